@@ -1,6 +1,9 @@
 use aegis_core::{Value, NativeFn};
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use lazy_static::lazy_static;
 use glfw::{Context, Glfw, PWindow, GlfwReceiver, WindowEvent}; // Note l'ajout de PWindow et GlfwReceiver
 
@@ -8,11 +11,673 @@ use glfw::{Context, Glfw, PWindow, GlfwReceiver, WindowEvent}; // Note l'ajout d
 
 struct GlfwState {
     context: Glfw,
+    // Thread ayant appelé glfw_init : GLFW interdit de terminer ailleurs
+    init_thread: std::thread::ThreadId,
     // CORRECTION TYPE : PWindow et GlfwReceiver
     windows: HashMap<usize, (PWindow, GlfwReceiver<(f64, WindowEvent)>)>,
     next_id: usize,
+    // Dernière géométrie (x, y, w, h) connue en mode "normal" (ni iconifiée, ni maximisée)
+    restored_geometry: HashMap<usize, (i32, i32, i32, i32)>,
+    // Valeur Aegis arbitraire attachée à chaque fenêtre
+    window_tags: HashMap<usize, Value>,
+    // Dernier mode vidéo vu sur le moniteur principal (pour glfw_monitor_changed)
+    last_video_mode: Option<glfw::VidMode>,
+    // Défilement et transitions touches/boutons cumulés depuis le dernier poll visible
+    frame_input: FrameInput,
+    // Suivi des entrées alimenté par poll, conservé d'un poll à l'autre
+    input: InputTracking,
+    // Si vrai, glfw_window_should_close pompe les événements lui-même
+    auto_poll: bool,
+    // Événements pompés par l'auto-poll, pas encore renvoyés par glfw_poll_events
+    pending_events: Vec<Value>,
+    // Limites de taille (min_w, min_h, max_w, max_h) posées par glfw_set_window_size_limits
+    size_limits: HashMap<usize, SizeLimits>,
+    // Instant (get_time) où chaque fenêtre est passée à should_close = true
+    close_times: HashMap<usize, f64>,
+    // Masque des catégories d'événements converties par poll (EVENT_*)
+    event_filter: i64,
+    // Registre des moniteurs : id Aegis -> pointeur GLFW (les ids ne sont jamais réutilisés)
+    monitors: HashMap<usize, *mut glfw::ffi::GLFWmonitor>,
+    next_monitor_id: usize,
+    // Fonction Aegis appelée à chaque (dé)connexion de moniteur
+    monitor_callback: Option<Value>,
+    // Appels de callbacks Aegis en attente, dépilés par glfw_next_callback
+    callback_queue: VecDeque<Value>,
+    // Fonction Aegis appelée avec (code, description) pour chaque erreur GLFW
+    error_callback: Option<Value>,
+    // Nombre d'événements vidés lors du dernier pump (avant filtrage)
+    last_drained: usize,
+    // Dernière taille connue de chaque fenêtre (initialisée à la création, mise à jour par poll)
+    window_sizes: HashMap<usize, (i32, i32)>,
+    // Callbacks Aegis par fenêtre
+    window_callbacks: HashMap<usize, WindowCallbacks>,
+    // File d'événements remplie par glfw_poll_events, consommée par glfw_next_event
+    event_queue: VecDeque<Value>,
+    // Horloge de jeu : instant de mise en pause (si en pause) et durée totale passée en pause
+    time_paused_at: Option<f64>,
+    paused_duration: f64,
+    // Géométrie fenêtrée (x, y, w, h) mémorisée au passage en plein écran
+    windowed_geometry: HashMap<usize, (i32, i32, i32, i32)>,
+    // Hash du dernier contenu vu du presse-papiers (None avant le premier appel)
+    clipboard_hash: Option<u64>,
+    // Durée (s) du dernier pump : poll_events + conversion des événements
+    last_poll_duration: f64,
+    // Mode de curseur courant de chaque fenêtre (CURSOR_*), GLFW n'étant pas interrogé
+    cursor_modes: HashMap<usize, i64>,
+    // Instant du dernier événement Size de chaque fenêtre
+    last_resize_times: HashMap<usize, f64>,
+    // Tampon de conversion réutilisé d'un poll à l'autre (sa capacité est conservée)
+    event_buffer: Vec<Value>,
+    // Si vrai, un échec de glfw_create_window est retenté avec CONTEXT_FALLBACKS
+    context_fallback: bool,
+    // Compteurs d'images de glfw_set_window_title_fps
+    fps_counters: HashMap<usize, FpsCounter>,
+    // Fenêtres ayant demandé l'attention sans avoir été refocalisées depuis
+    attention_pending: HashSet<usize>,
+    // Si vrai, le prochain Focus d'une fenêtre en attente efface explicitement la demande
+    auto_clear_attention: bool,
+    // Fenêtres suivies par glfw_enable_dpi_autoscale
+    dpi_autoscale: HashMap<usize, DpiAutoscale>,
+    // Rapport largeur/hauteur imposé (numer, denom) ; absent = libre
+    aspect_ratios: HashMap<usize, (u32, u32)>,
+    // Dernier titre posé sur chaque fenêtre (GLFW n'a pas de getter)
+    window_titles: HashMap<usize, String>,
+    // Fenêtres dont le prochain événement Close doit être avalé (glfw_ignore_next_close)
+    ignore_next_close: HashSet<usize>,
+    // Taille maximale (octets) lue par glfw_get_clipboard_string ; None = illimitée
+    clipboard_read_limit: Option<usize>,
+    // Instant du dernier appel à glfw_frame_delta (None avant le premier, ou après glfw_set_time)
+    last_frame_time: Option<f64>,
+    // État redimensionnable de chaque fenêtre (hint à la création, puis setters)
+    resizable: HashMap<usize, bool>,
+    // Hints POSITION_X / POSITION_Y et VISIBLE courants, pour le repli avant GLFW 3.4
+    position_hint: (Option<i32>, Option<i32>),
+    visible_hint: bool,
+    // Prochain numéro de séquence ("seq") attribué à un enregistrement d'événement
+    next_event_seq: u64,
+    // Zone (x, y, w, h) de la zone client où le curseur est caché (glfw_set_cursor_hide_region)
+    cursor_hide_regions: HashMap<usize, (f64, f64, f64, f64)>,
+    // Fondus d'opacité en cours, avancés par pump_events (glfw_fade_window)
+    fades: HashMap<usize, Fade>,
+    // Si vrai, pump_events détruit les fenêtres passées à should_close (glfw_set_auto_destroy_closed)
+    auto_destroy_closed: bool,
+    // Pas fixe (glfw_fixed_timestep) : instant du dernier appel et temps restant à simuler
+    fixed_last_time: Option<f64>,
+    fixed_accumulator: f64,
+    // Fenêtre ayant le focus d'après les événements Focus (0 = aucune des nôtres)
+    focused_window: usize,
+    // Événements traités au plus par fenêtre et par poll (None = illimité) ; le reste attend
+    // dans la file GLFW de la fenêtre jusqu'au poll suivant
+    max_events_per_window: Option<usize>,
+    // Vrai si le dernier poll a atteint ce plafond pour au moins une fenêtre
+    events_deferred: bool,
+    // Valeur brute du timer GLFW à l'init : origine de "uptime_seconds", insensible à glfw_set_time
+    init_timer_value: u64,
+    // Modes de curseur sauvegardés par glfw_push_cursor_mode (le sommet est le plus récent)
+    cursor_mode_stacks: HashMap<usize, Vec<i64>>,
+    // Nombre d'intervalles de la moyenne glissante des FPS (glfw_set_fps_sample_window)
+    fps_sample_window: usize,
+    // Si vrai, les enregistrements "key" portent aussi le nom de la touche (glfw_set_event_key_names)
+    event_key_names: bool,
+    // Délai de grâce (s) avant qu'une demande de fermeture prenne effet (glfw_set_close_grace)
+    close_graces: HashMap<usize, f64>,
+    // Instant du premier Close d'une demande de fermeture encore en attente de confirmation
+    close_requests: HashMap<usize, f64>,
+    // Fenêtre dont le contexte OpenGL est courant sur le thread principal (0 = aucune)
+    current_context: usize,
 }
 
+// Callbacks Aegis d'une fenêtre (None = pas de callback)
+#[derive(Default)]
+struct WindowCallbacks {
+    scroll: Option<Value>,
+    key: Option<Value>,
+    mouse_button: Option<Value>,
+    cursor_pos: Option<Value>,
+    char: Option<Value>,
+}
+
+// None = GLFW_DONT_CARE
+#[derive(Clone, Copy, Default)]
+struct SizeLimits {
+    min_width: Option<u32>,
+    min_height: Option<u32>,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+}
+
+impl SizeLimits {
+    // Min > max (souvent une inversion d'arguments) : comportement propre à chaque gestionnaire de fenêtres
+    fn validate(&self) -> Result<(), String> {
+        for (axis, min, max) in [
+            ("width", self.min_width, self.max_width),
+            ("height", self.min_height, self.max_height),
+        ] {
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(format!("Invalid size limits: min {} {} is greater than max {} {}", axis, min, axis, max));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Moyenne glissante des intervalles entre deux swaps de la fenêtre (voir record_frame)
+#[derive(Default)]
+struct FpsCounter {
+    last_time: Option<f64>,
+    deltas: VecDeque<f64>,
+}
+
+impl FpsCounter {
+    // Enregistre une image à l'instant `now`, en gardant au plus `samples` intervalles
+    fn record(&mut self, now: f64, samples: usize) {
+        if let Some(last) = self.last_time.replace(now) {
+            self.deltas.push_back(now - last);
+        }
+        while self.deltas.len() > samples {
+            self.deltas.pop_front();
+        }
+    }
+
+    // FPS moyens sur les `samples` derniers intervalles (0.0 sans intervalle mesuré)
+    fn average(&self, samples: usize) -> f64 {
+        let recent = self.deltas.len().min(samples);
+        let total: f64 = self.deltas.iter().rev().take(recent).sum();
+        if total > 0.0 { recent as f64 / total } else { 0.0 }
+    }
+}
+
+// Interpolation linéaire de l'opacité entre start et start + duration (horloge GLFW)
+struct Fade {
+    from: f32,
+    to: f32,
+    start: f64,
+    duration: f64,
+}
+
+// Transitions observées pendant un poll (codes GLFW). Un appui et un relâchement
+// dans le même poll sont tous deux visibles, même à faible framerate.
+#[derive(Default)]
+struct InputEdges {
+    pressed: HashSet<i32>,
+    released: HashSet<i32>,
+}
+
+impl InputEdges {
+    // La répétition OS n'est pas une transition
+    fn record(&mut self, action: glfw::Action, code: i32) {
+        match action {
+            glfw::Action::Press => { self.pressed.insert(code); }
+            glfw::Action::Release => { self.released.insert(code); }
+            glfw::Action::Repeat => {}
+        }
+    }
+}
+
+// Accumulateurs d'entrées d'une "frame" : tout ce qui a été pompé depuis le dernier poll
+// visible (glfw_poll_events, glfw_poll_events_array, glfw_poll_events_grouped). Les
+// auto-polls de glfw_window_should_close s'y ajoutent au lieu de les remettre à zéro.
+#[derive(Default)]
+struct FrameInput {
+    // Défilement cumulé par fenêtre
+    scroll_deltas: HashMap<usize, (f64, f64)>,
+    // Touches passées à appuyé / relâché, par fenêtre
+    key_edges: HashMap<usize, InputEdges>,
+    // Idem pour les boutons de souris
+    mouse_button_edges: HashMap<usize, InputEdges>,
+    // Vrai une fois la frame rendue visible : le prochain pompage repart de zéro
+    stale: bool,
+}
+
+impl FrameInput {
+    fn begin_pump(&mut self) {
+        if self.stale {
+            self.scroll_deltas.clear();
+            self.key_edges.clear();
+            self.mouse_button_edges.clear();
+            self.stale = false;
+        }
+    }
+
+    fn end_poll(&mut self) {
+        self.stale = true;
+    }
+
+    fn remove(&mut self, id: usize) {
+        self.scroll_deltas.remove(&id);
+        self.key_edges.remove(&id);
+        self.mouse_button_edges.remove(&id);
+    }
+}
+
+// Suivi des entrées conservé d'un poll à l'autre (jamais remis à zéro par begin_pump)
+#[derive(Default)]
+struct InputTracking {
+    // Modificateurs du dernier événement clavier/souris par fenêtre
+    current_mods: HashMap<usize, i32>,
+    // Touches enfoncées d'après les événements Key vus pendant poll (glfw_get_keys_pressed)
+    held_keys: HashMap<usize, HashSet<i32>>,
+    // Dernière position du curseur vue par poll, et déplacement cumulé depuis la dernière lecture
+    cursor_last: HashMap<usize, (f64, f64)>,
+    cursor_deltas: HashMap<usize, (f64, f64)>,
+    // Deux derniers événements CursorPos (time, x, y) de chaque fenêtre, du plus ancien au plus récent
+    cursor_samples: HashMap<usize, [(f64, f64, f64); 2]>,
+}
+
+impl InputTracking {
+    fn remove(&mut self, id: usize) {
+        self.current_mods.remove(&id);
+        self.held_keys.remove(&id);
+        self.cursor_last.remove(&id);
+        self.cursor_deltas.remove(&id);
+        self.cursor_samples.remove(&id);
+    }
+}
+
+// Mise à jour des accumulateurs pour un événement, sans toucher à la fenêtre : appelée
+// une fois par événement dans la boucle de pump_events, et utilisable hors GLFW
+fn accumulate_input(frame: &mut FrameInput, input: &mut InputTracking, id: usize, time: f64, event: &WindowEvent) {
+    match *event {
+        WindowEvent::Scroll(dx, dy) => {
+            let delta = frame.scroll_deltas.entry(id).or_insert((0.0, 0.0));
+            delta.0 += dx;
+            delta.1 += dy;
+        }
+        WindowEvent::Key(key, _, action, mods) => {
+            input.current_mods.insert(id, mods_code(mods) as i32);
+            frame.key_edges.entry(id).or_default().record(action, key as i32);
+            // Key::Unknown (-1) regroupe plusieurs touches : impossible à suivre
+            if key != glfw::Key::Unknown {
+                let held = input.held_keys.entry(id).or_default();
+                match action {
+                    glfw::Action::Press => { held.insert(key as i32); }
+                    glfw::Action::Release => { held.remove(&(key as i32)); }
+                    glfw::Action::Repeat => {}
+                }
+            }
+        }
+        WindowEvent::MouseButton(button, action, mods) => {
+            input.current_mods.insert(id, mods_code(mods) as i32);
+            frame.mouse_button_edges.entry(id).or_default().record(action, button as i32);
+        }
+        WindowEvent::CursorPos(x, y) => {
+            // Premier échantillon dupliqué : vitesse nulle tant qu'un second n'est pas arrivé
+            let samples = input.cursor_samples.entry(id).or_insert([(time, x, y); 2]);
+            *samples = [samples[1], (time, x, y)];
+            // En mode DISABLED les positions sont virtuelles : le delta reste continu
+            if let Some((last_x, last_y)) = input.cursor_last.insert(id, (x, y)) {
+                let delta = input.cursor_deltas.entry(id).or_insert((0.0, 0.0));
+                delta.0 += x - last_x;
+                delta.1 += y - last_y;
+            }
+        }
+        _ => {}
+    }
+}
+
+// Suivi DPI d'une fenêtre : échelle courante et taille logique (taille / échelle) à préserver
+struct DpiAutoscale {
+    scale: (f32, f32),
+    logical_size: (f64, f64),
+}
+
+// Largeur (px) de barre de titre qui doit rester sur un écran pour que la fenêtre reste attrapable
+const SAFE_TITLE_VISIBLE: i32 = 32;
+
+// Au-delà de ce délai (s) sans mouvement, le curseur est considéré comme arrêté
+const CURSOR_VELOCITY_TIMEOUT: f64 = 0.1;
+
+// Pas simulés au plus par frame : au-delà (pause du débogueur, chargement), le retard est abandonné
+const MAX_FIXED_STEPS: i64 = 8;
+
+// Nombre d'intervalles moyennés par défaut pour les FPS (assez pour lisser sans retard visible)
+const FPS_SAMPLES: usize = 30;
+
+// Version de GLFW (majeure, mineure) ayant introduit chaque fonctionnalité optionnelle
+const FEATURE_VERSIONS: &[(&str, (u64, u64))] = &[
+    ("raw_mouse_motion", (3, 3)),
+    ("window_opacity", (3, 3)),
+    ("content_scale", (3, 3)),
+    ("window_attention", (3, 3)),
+    ("transparent_framebuffer", (3, 3)),
+    ("window_hovered", (3, 3)),
+    ("gamepad", (3, 3)),
+    ("maximize_event", (3, 3)),
+    ("focus_on_show", (3, 3)),
+    ("mouse_passthrough", (3, 4)),
+    ("platform", (3, 4)),
+    ("position_hint", (3, 4)),
+    ("scale_framebuffer", (3, 4)),
+];
+
+// Codes de touches GLFW (nom sans le préfixe KEY_, code entier)
+// Source unique pour la validation int -> glfw::Key
+const KEY_CODES: &[(&str, i32)] = &[
+    ("SPACE", 32),
+    ("APOSTROPHE", 39),
+    ("COMMA", 44),
+    ("MINUS", 45),
+    ("PERIOD", 46),
+    ("SLASH", 47),
+    ("0", 48),
+    ("1", 49),
+    ("2", 50),
+    ("3", 51),
+    ("4", 52),
+    ("5", 53),
+    ("6", 54),
+    ("7", 55),
+    ("8", 56),
+    ("9", 57),
+    ("SEMICOLON", 59),
+    ("EQUAL", 61),
+    ("A", 65),
+    ("B", 66),
+    ("C", 67),
+    ("D", 68),
+    ("E", 69),
+    ("F", 70),
+    ("G", 71),
+    ("H", 72),
+    ("I", 73),
+    ("J", 74),
+    ("K", 75),
+    ("L", 76),
+    ("M", 77),
+    ("N", 78),
+    ("O", 79),
+    ("P", 80),
+    ("Q", 81),
+    ("R", 82),
+    ("S", 83),
+    ("T", 84),
+    ("U", 85),
+    ("V", 86),
+    ("W", 87),
+    ("X", 88),
+    ("Y", 89),
+    ("Z", 90),
+    ("LEFT_BRACKET", 91),
+    ("BACKSLASH", 92),
+    ("RIGHT_BRACKET", 93),
+    ("GRAVE_ACCENT", 96),
+    ("WORLD_1", 161),
+    ("WORLD_2", 162),
+    ("ESCAPE", 256),
+    ("ENTER", 257),
+    ("TAB", 258),
+    ("BACKSPACE", 259),
+    ("INSERT", 260),
+    ("DELETE", 261),
+    ("RIGHT", 262),
+    ("LEFT", 263),
+    ("DOWN", 264),
+    ("UP", 265),
+    ("PAGE_UP", 266),
+    ("PAGE_DOWN", 267),
+    ("HOME", 268),
+    ("END", 269),
+    ("CAPS_LOCK", 280),
+    ("SCROLL_LOCK", 281),
+    ("NUM_LOCK", 282),
+    ("PRINT_SCREEN", 283),
+    ("PAUSE", 284),
+    ("F1", 290),
+    ("F2", 291),
+    ("F3", 292),
+    ("F4", 293),
+    ("F5", 294),
+    ("F6", 295),
+    ("F7", 296),
+    ("F8", 297),
+    ("F9", 298),
+    ("F10", 299),
+    ("F11", 300),
+    ("F12", 301),
+    ("F13", 302),
+    ("F14", 303),
+    ("F15", 304),
+    ("F16", 305),
+    ("F17", 306),
+    ("F18", 307),
+    ("F19", 308),
+    ("F20", 309),
+    ("F21", 310),
+    ("F22", 311),
+    ("F23", 312),
+    ("F24", 313),
+    ("F25", 314),
+    ("KP_0", 320),
+    ("KP_1", 321),
+    ("KP_2", 322),
+    ("KP_3", 323),
+    ("KP_4", 324),
+    ("KP_5", 325),
+    ("KP_6", 326),
+    ("KP_7", 327),
+    ("KP_8", 328),
+    ("KP_9", 329),
+    ("KP_DECIMAL", 330),
+    ("KP_DIVIDE", 331),
+    ("KP_MULTIPLY", 332),
+    ("KP_SUBTRACT", 333),
+    ("KP_ADD", 334),
+    ("KP_ENTER", 335),
+    ("KP_EQUAL", 336),
+    ("LEFT_SHIFT", 340),
+    ("LEFT_CONTROL", 341),
+    ("LEFT_ALT", 342),
+    ("LEFT_SUPER", 343),
+    ("RIGHT_SHIFT", 344),
+    ("RIGHT_CONTROL", 345),
+    ("RIGHT_ALT", 346),
+    ("RIGHT_SUPER", 347),
+    ("MENU", 348),
+];
+
+// Codes de boutons souris GLFW (nom sans le préfixe MOUSE_BUTTON_, code entier)
+const MOUSE_BUTTON_CODES: &[(&str, i32)] = &[
+    ("1", 0),
+    ("2", 1),
+    ("3", 2),
+    ("4", 3),
+    ("5", 4),
+    ("6", 5),
+    ("7", 6),
+    ("8", 7),
+    ("LEFT", 0),
+    ("RIGHT", 1),
+    ("MIDDLE", 2),
+];
+
+// Codes entiers des types d'enregistrement (champ "type_code", à côté de "type").
+// Propres au plugin : stables, un nouveau type prend le code suivant.
+const EVENT_TYPE_CODES: &[(&str, i64)] = &[
+    ("key", 1),
+    ("char", 2),
+    ("mouse_button", 3),
+    ("cursor_pos", 4),
+    ("scroll", 5),
+    ("pos", 6),
+    ("size", 7),
+    ("framebuffer_size", 8),
+    ("close", 9),
+    ("focus", 10),
+    ("iconify", 11),
+    ("maximize", 12),
+    ("joystick", 13),
+    ("monitor", 14),
+    ("user", 15),
+    ("destroyed", 16),
+];
+
+// Actions des événements key / mouse_button (doit rester aligné sur action_code)
+const ACTION_CODES: &[(&str, i64)] = &[
+    ("RELEASE", 0),
+    ("PRESS", 1),
+    ("REPEAT", 2),
+];
+
+// Window hints / attributs GLFW (nom sans le préfixe, valeur native de glfw3.h)
+// Source unique : glfw_window_hint et glfw_set_window_attrib n'acceptent que ces codes
+const WINDOW_HINT_CODES: &[(&str, i64)] = &[
+    ("FOCUSED", 0x00020001),
+    ("RESIZABLE", 0x00020003),
+    ("VISIBLE", 0x00020004),
+    ("DECORATED", 0x00020005),
+    ("AUTO_ICONIFY", 0x00020006),
+    ("FLOATING", 0x00020007),
+    ("MAXIMIZED", 0x00020008),
+    ("TRANSPARENT_FRAMEBUFFER", 0x0002000A),
+    ("HOVERED", 0x0002000B),
+    ("FOCUS_ON_SHOW", 0x0002000C),
+    ("MOUSE_PASSTHROUGH", 0x0002000D),
+    ("POSITION_X", 0x0002000E),
+    ("POSITION_Y", 0x0002000F),
+    ("SAMPLES", 0x0002100D),
+    ("DOUBLEBUFFER", 0x00021010),
+    ("CLIENT_API", 0x00022001),
+    ("CONTEXT_VERSION_MAJOR", 0x00022002),
+    ("CONTEXT_VERSION_MINOR", 0x00022003),
+    ("OPENGL_FORWARD_COMPAT", 0x00022006),
+    ("OPENGL_PROFILE", 0x00022008),
+    ("CONTEXT_RELEASE_BEHAVIOR", 0x00022009),
+    ("CONTEXT_NO_ERROR", 0x0002200A),
+    ("CONTEXT_CREATION_API", 0x0002200B),
+    ("SCALE_TO_MONITOR", 0x0002200C),
+    ("SCALE_FRAMEBUFFER", 0x0002200D),
+];
+
+// Attributs de fenêtre plus récents que GLFW 3.2 : fonctionnalité de FEATURE_VERSIONS requise
+const ATTRIB_FEATURES: &[(&str, &str)] = &[
+    ("TRANSPARENT_FRAMEBUFFER", "transparent_framebuffer"),
+    ("HOVERED", "window_hovered"),
+    ("FOCUS_ON_SHOW", "focus_on_show"),
+    ("MOUSE_PASSTHROUGH", "mouse_passthrough"),
+    ("POSITION_X", "position_hint"),
+    ("POSITION_Y", "position_hint"),
+    ("SCALE_FRAMEBUFFER", "scale_framebuffer"),
+];
+
+// GLFW_ANY_POSITION : valeur par défaut des hints POSITION_X / POSITION_Y
+const ANY_POSITION: i64 = 0x80000000;
+
+// Axes du layout standard de manette (mapping SDL_GameControllerDB appliqué par GLFW)
+const GAMEPAD_AXES: &[(&str, glfw::GamepadAxis)] = &[
+    ("left_x", glfw::GamepadAxis::AxisLeftX),
+    ("left_y", glfw::GamepadAxis::AxisLeftY),
+    ("right_x", glfw::GamepadAxis::AxisRightX),
+    ("right_y", glfw::GamepadAxis::AxisRightY),
+    ("left_trigger", glfw::GamepadAxis::AxisLeftTrigger),
+    ("right_trigger", glfw::GamepadAxis::AxisRightTrigger),
+];
+
+// Modes de curseur (valeurs natives de glfw3.h)
+const CURSOR_NORMAL: i64 = 0x00034001;
+const CURSOR_HIDDEN: i64 = 0x00034002;
+const CURSOR_DISABLED: i64 = 0x00034003;
+
+// Modes d'entrée de glfwSetInputMode (valeurs de glfw3.h)
+const INPUT_MODE_CURSOR: i64 = 0x00033001;
+const INPUT_MODE_STICKY_KEYS: i64 = 0x00033002;
+const INPUT_MODE_STICKY_MOUSE_BUTTONS: i64 = 0x00033003;
+const INPUT_MODE_LOCK_KEY_MODS: i64 = 0x00033004;
+const INPUT_MODE_RAW_MOUSE_MOTION: i64 = 0x00033005;
+
+// Bits du champ "mods" (disposition de glfw3.h, identique pour clavier et souris).
+// CAPS_LOCK / NUM_LOCK n'apparaissent qu'avec INPUT_MODE_LOCK_KEY_MODS (GLFW 3.3+).
+const MOD_BITS: &[(&str, i64)] = &[
+    ("shift", 0x0001),
+    ("control", 0x0002),
+    ("alt", 0x0004),
+    ("super", 0x0008),
+    ("caps_lock", 0x0010),
+    ("num_lock", 0x0020),
+];
+
+// Plus grande valeur acceptée par glfwSetTime (limite documentée de GLFW)
+const MAX_GLFW_TIME: f64 = 18446744073.0;
+
+// Dimension maximale acceptée pour une fenêtre (bien au-delà de tout écran réel)
+const MAX_WINDOW_DIMENSION: i64 = 65536;
+
+// Catégories d'événements pour glfw_set_event_filter (bitmask)
+const EVENT_KEYS: i64 = 1; // key, char
+const EVENT_MOUSE: i64 = 2; // mouse_button, cursor_pos, scroll, cursor_enter
+const EVENT_WINDOW: i64 = 4; // pos, size, close, focus, ...
+const EVENT_DROP: i64 = 8; // file_drop
+const EVENT_JOYSTICK: i64 = 16; // joystick (global)
+const EVENT_MONITOR: i64 = 32; // monitor (global)
+const EVENT_USER: i64 = 64; // user (glfw_post_user_event)
+const EVENT_ALL: i64 = EVENT_KEYS | EVENT_MOUSE | EVENT_WINDOW | EVENT_DROP | EVENT_JOYSTICK | EVENT_MONITOR | EVENT_USER;
+
+// Codes GLFW des init hints (valeurs natives de glfw3.h)
+const INIT_HINT_JOYSTICK_HAT_BUTTONS: i64 = 0x00050001;
+const INIT_HINT_ANGLE_PLATFORM_TYPE: i64 = 0x00050002;
+const INIT_HINT_PLATFORM: i64 = 0x00050003;
+const INIT_HINT_COCOA_CHDIR_RESOURCES: i64 = 0x00051001;
+const INIT_HINT_COCOA_MENUBAR: i64 = 0x00051002;
+
+// Tables nom -> code des constantes ci-dessus, pour glfw_constants
+const INIT_HINT_CODES: &[(&str, i64)] = &[
+    ("JOYSTICK_HAT_BUTTONS", INIT_HINT_JOYSTICK_HAT_BUTTONS),
+    ("ANGLE_PLATFORM_TYPE", INIT_HINT_ANGLE_PLATFORM_TYPE),
+    ("PLATFORM", INIT_HINT_PLATFORM),
+    ("COCOA_CHDIR_RESOURCES", INIT_HINT_COCOA_CHDIR_RESOURCES),
+    ("COCOA_MENUBAR", INIT_HINT_COCOA_MENUBAR),
+];
+
+const INPUT_MODE_CODES: &[(&str, i64)] = &[
+    ("CURSOR", INPUT_MODE_CURSOR),
+    ("STICKY_KEYS", INPUT_MODE_STICKY_KEYS),
+    ("STICKY_MOUSE_BUTTONS", INPUT_MODE_STICKY_MOUSE_BUTTONS),
+    ("LOCK_KEY_MODS", INPUT_MODE_LOCK_KEY_MODS),
+    ("RAW_MOUSE_MOTION", INPUT_MODE_RAW_MOUSE_MOTION),
+];
+
+const CURSOR_MODE_CODES: &[(&str, i64)] = &[
+    ("NORMAL", CURSOR_NORMAL),
+    ("HIDDEN", CURSOR_HIDDEN),
+    ("DISABLED", CURSOR_DISABLED),
+];
+
+const EVENT_FILTER_CODES: &[(&str, i64)] = &[
+    ("KEYS", EVENT_KEYS),
+    ("MOUSE", EVENT_MOUSE),
+    ("WINDOW", EVENT_WINDOW),
+    ("DROP", EVENT_DROP),
+    ("JOYSTICK", EVENT_JOYSTICK),
+    ("MONITOR", EVENT_MONITOR),
+    ("USER", EVENT_USER),
+    ("ALL", EVENT_ALL),
+];
+
+// Plateformes GLFW 3.4 (nom renvoyé par glfw_get_platform, valeur native de glfw3.h)
+const PLATFORM_CODES: &[(&str, i64)] = &[
+    ("win32", 0x00060001),
+    ("cocoa", 0x00060002),
+    ("wayland", 0x00060003),
+    ("x11", 0x00060004),
+    ("null", 0x00060005),
+];
+
+// Contextes essayés dans l'ordre quand la création échoue et que le repli est activé
+// (api, version majeure, version mineure, profil)
+const CONTEXT_FALLBACKS: &[(glfw::ClientApiHint, u32, u32, glfw::OpenGlProfileHint)] = &[
+    (glfw::ClientApiHint::OpenGl, 4, 6, glfw::OpenGlProfileHint::Core),
+    (glfw::ClientApiHint::OpenGl, 3, 3, glfw::OpenGlProfileHint::Core),
+    (glfw::ClientApiHint::OpenGl, 2, 1, glfw::OpenGlProfileHint::Any),
+    (glfw::ClientApiHint::OpenGlEs, 2, 0, glfw::OpenGlProfileHint::Any),
+];
+
+// Nombre d'erreurs conservées par glfw_get_error_log
+const ERROR_LOG_SIZE: usize = 32;
+
+// Nombre maximal d'événements gardés en attente (auto-poll, file de glfw_next_event)
+const MAX_PENDING_EVENTS: usize = 4096;
+
 // --- LE HACK POUR LE SEND ---
 // On crée un wrapper vide qui contient notre état
 struct ThreadSafeState(GlfwState);
@@ -21,9 +686,43 @@ struct ThreadSafeState(GlfwState);
 // (C'est nécessaire car GLFW utilise des pointeurs C bruts *mut void)
 unsafe impl Send for ThreadSafeState {}
 
+// Payload de glfw_post_user_event. Value n'est pas Send (Array/Map partagent des Rc),
+// mais glfw_post_user_event n'accepte que des scalaires (null, bool, int, float, string),
+// qui ne contiennent aucun Rc : les déplacer d'un thread à l'autre est sûr.
+struct UserEvent(Value);
+unsafe impl Send for UserEvent {}
+
+// Stratégie d'ids de fenêtres (glfw_set_id_strategy) : hors de STATE pour survivre à glfw_terminate.
+// En mode monotone, glfw_init reprend après le plus grand id jamais attribué.
+static MONOTONIC_WINDOW_IDS: AtomicBool = AtomicBool::new(false);
+static LAST_WINDOW_ID: AtomicUsize = AtomicUsize::new(0);
+// Idem pour les numéros de séquence d'événements (dernier numéro attribué)
+static LAST_EVENT_SEQ: AtomicU64 = AtomicU64::new(0);
+// Vrai entre glfw_init et glfw_terminate, lu sans verrou par glfw_get_time
+static GLFW_INITIALIZED: AtomicBool = AtomicBool::new(false);
+
 lazy_static! {
-    // On utilise notre wrapper ThreadSafeState
+    // On utilise notre wrapper ThreadSafeState.
+    // Le Mutex n'est pas réentrant : aucune native ne doit appeler Aegis (ni une autre native)
+    // tant qu'elle le tient. Les callbacks Aegis passent donc toujours par callback_queue.
     static ref STATE: Mutex<Option<ThreadSafeState>> = Mutex::new(None);
+
+    // File séparée remplie par le callback joystick de GLFW.
+    // Le callback tourne pendant poll_events (STATE déjà verrouillé), d'où un Mutex à part.
+    static ref JOYSTICK_EVENTS: Mutex<Vec<(i32, bool)>> = Mutex::new(Vec::new());
+
+    // Idem pour le callback moniteur : (pointeur GLFWmonitor, connecté)
+    static ref MONITOR_EVENTS: Mutex<Vec<(usize, bool)>> = Mutex::new(Vec::new());
+
+    // Erreurs GLFW : la dernière (lisible même avant init) et celles à transmettre au callback
+    static ref LAST_ERROR: Mutex<Option<(i32, String)>> = Mutex::new(None);
+    static ref PENDING_ERRORS: Mutex<Vec<(i32, String)>> = Mutex::new(Vec::new());
+    // Historique borné des ERROR_LOG_SIZE dernières erreurs (hors STATE : couvre aussi glfw_init)
+    static ref ERROR_LOG: Mutex<VecDeque<(i32, String)>> = Mutex::new(VecDeque::new());
+
+    // Événements utilisateur postés depuis n'importe quel thread, vidés par le prochain poll.
+    // File à part : le thread principal peut tenir STATE (ou attendre dans GLFW) pendant un post.
+    static ref USER_EVENTS: Mutex<VecDeque<UserEvent>> = Mutex::new(VecDeque::new());
 }
 
 // --- REGISTRATION ---
@@ -34,133 +733,5066 @@ pub extern "C" fn _aegis_register(map: &mut HashMap<String, NativeFn>) {
     map.insert("glfw_create_window".to_string(), glfw_create_window);
     map.insert("glfw_window_should_close".to_string(), glfw_window_should_close);
     map.insert("glfw_poll_events".to_string(), glfw_poll_events);
+    map.insert("glfw_poll_events_array".to_string(), glfw_poll_events_array);
     map.insert("glfw_swap_buffers".to_string(), glfw_swap_buffers);
     map.insert("glfw_get_proc_address".to_string(), glfw_get_proc_address);
     map.insert("glfw_get_key".to_string(), glfw_get_key);
     map.insert("glfw_get_time".to_string(), glfw_get_time);
+    map.insert("glfw_get_restored_geometry".to_string(), glfw_get_restored_geometry);
+    map.insert("glfw_get_joystick_guid".to_string(), glfw_get_joystick_guid);
+    map.insert("glfw_set_window_tag".to_string(), glfw_set_window_tag);
+    map.insert("glfw_get_window_tag".to_string(), glfw_get_window_tag);
+    map.insert("glfw_has_feature".to_string(), glfw_has_feature);
+    map.insert("glfw_monitor_changed".to_string(), glfw_monitor_changed);
+    map.insert("glfw_get_input_state".to_string(), glfw_get_input_state);
+    map.insert("glfw_terminate".to_string(), glfw_terminate);
+    map.insert("glfw_set_auto_poll".to_string(), glfw_set_auto_poll);
+    map.insert("glfw_set_clipboard_string".to_string(), glfw_set_clipboard_string);
+    map.insert("glfw_get_clipboard_string".to_string(), glfw_get_clipboard_string);
+    map.insert("glfw_set_window_size_limits".to_string(), glfw_set_window_size_limits);
+    map.insert("glfw_set_window_size".to_string(), glfw_set_window_size);
+    map.insert("glfw_window_close_time".to_string(), glfw_window_close_time);
+    map.insert("glfw_set_window_opacity".to_string(), glfw_set_window_opacity);
+    map.insert("glfw_get_window_opacity".to_string(), glfw_get_window_opacity);
+    map.insert("glfw_set_event_filter".to_string(), glfw_set_event_filter);
+    map.insert("glfw_get_proc_addresses".to_string(), glfw_get_proc_addresses);
+    map.insert("glfw_init_hint".to_string(), glfw_init_hint);
+    map.insert("glfw_windows_should_close".to_string(), glfw_windows_should_close);
+    map.insert("glfw_set_monitor_callback".to_string(), glfw_set_monitor_callback);
+    map.insert("glfw_next_callback".to_string(), glfw_next_callback);
+    map.insert("glfw_window_hint".to_string(), glfw_window_hint);
+    map.insert("glfw_set_window_attrib".to_string(), glfw_set_window_attrib);
+    map.insert("glfw_set_error_callback".to_string(), glfw_set_error_callback);
+    map.insert("glfw_get_error".to_string(), glfw_get_error);
+    map.insert("glfw_get_window_size".to_string(), glfw_get_window_size);
+    map.insert("glfw_get_window_size_live".to_string(), glfw_get_window_size_live);
+    map.insert("glfw_is_window_iconified".to_string(), glfw_is_window_iconified);
+    map.insert("glfw_get_framebuffer_size".to_string(), glfw_get_framebuffer_size);
+    map.insert("glfw_vulkan_supported".to_string(), glfw_vulkan_supported);
+    map.insert("glfw_get_required_instance_extensions".to_string(), glfw_get_required_instance_extensions);
+    map.insert("glfw_set_scroll_callback".to_string(), glfw_set_scroll_callback);
+    map.insert("glfw_set_key_callback".to_string(), glfw_set_key_callback);
+    map.insert("glfw_set_mouse_button_callback".to_string(), glfw_set_mouse_button_callback);
+    map.insert("glfw_set_cursor_pos_callback".to_string(), glfw_set_cursor_pos_callback);
+    map.insert("glfw_set_char_callback".to_string(), glfw_set_char_callback);
+    map.insert("glfw_next_event".to_string(), glfw_next_event);
+    map.insert("glfw_pause_time".to_string(), glfw_pause_time);
+    map.insert("glfw_resume_time".to_string(), glfw_resume_time);
+    map.insert("glfw_get_game_time".to_string(), glfw_get_game_time);
+    map.insert("glfw_set_window_monitor".to_string(), glfw_set_window_monitor);
+    map.insert("glfw_clipboard_changed".to_string(), glfw_clipboard_changed);
+    map.insert("glfw_get_window_states".to_string(), glfw_get_window_states);
+    map.insert("glfw_last_poll_duration".to_string(), glfw_last_poll_duration);
+    map.insert("glfw_set_cursor_mode".to_string(), glfw_set_cursor_mode);
+    map.insert("glfw_set_cursor_pos".to_string(), glfw_set_cursor_pos);
+    map.insert("glfw_center_window".to_string(), glfw_center_window);
+    map.insert("glfw_get_resize_settled".to_string(), glfw_get_resize_settled);
+    map.insert("glfw_get_primary_monitor".to_string(), glfw_get_primary_monitor);
+    map.insert("glfw_get_monitors".to_string(), glfw_get_monitors);
+    map.insert("glfw_set_time".to_string(), glfw_set_time);
+    map.insert("glfw_get_cursor_delta".to_string(), glfw_get_cursor_delta);
+    map.insert("glfw_get_joystick_axes".to_string(), glfw_get_joystick_axes);
+    map.insert("glfw_get_joystick_axes_deadzone".to_string(), glfw_get_joystick_axes_deadzone);
+    map.insert("glfw_set_context_fallback".to_string(), glfw_set_context_fallback);
+    map.insert("glfw_get_context_version".to_string(), glfw_get_context_version);
+    map.insert("glfw_set_window_title".to_string(), glfw_set_window_title);
+    map.insert("glfw_set_window_title_fps".to_string(), glfw_set_window_title_fps);
+    map.insert("glfw_set_input_mode".to_string(), glfw_set_input_mode);
+    map.insert("glfw_get_input_mode".to_string(), glfw_get_input_mode);
+    map.insert("glfw_request_window_attention".to_string(), glfw_request_window_attention);
+    map.insert("glfw_set_auto_clear_attention".to_string(), glfw_set_auto_clear_attention);
+    map.insert("glfw_get_platform".to_string(), glfw_get_platform);
+    map.insert("glfw_platform_supported".to_string(), glfw_platform_supported);
+    map.insert("glfw_enable_dpi_autoscale".to_string(), glfw_enable_dpi_autoscale);
+    map.insert("glfw_key_just_pressed".to_string(), glfw_key_just_pressed);
+    map.insert("glfw_key_just_released".to_string(), glfw_key_just_released);
+    map.insert("glfw_mouse_button_just_pressed".to_string(), glfw_mouse_button_just_pressed);
+    map.insert("glfw_mouse_button_just_released".to_string(), glfw_mouse_button_just_released);
+    map.insert("glfw_set_window_aspect_ratio".to_string(), glfw_set_window_aspect_ratio);
+    map.insert("glfw_get_window_aspect_ratio".to_string(), glfw_get_window_aspect_ratio);
+    map.insert("glfw_get_window_title".to_string(), glfw_get_window_title);
+    map.insert("glfw_get_joystick_battery".to_string(), glfw_get_joystick_battery);
+    map.insert("glfw_swap_buffers_timed".to_string(), glfw_swap_buffers_timed);
+    map.insert("glfw_create_window_ex".to_string(), glfw_create_window_ex);
+    map.insert("glfw_set_window_should_close".to_string(), glfw_set_window_should_close);
+    map.insert("glfw_ignore_next_close".to_string(), glfw_ignore_next_close);
+    map.insert("glfw_post_user_event".to_string(), glfw_post_user_event);
+    map.insert("glfw_set_clipboard_read_limit".to_string(), glfw_set_clipboard_read_limit);
+    map.insert("glfw_wait_events_timeout".to_string(), glfw_wait_events_timeout);
+    map.insert("glfw_get_window_attrib".to_string(), glfw_get_window_attrib);
+    map.insert("glfw_use_opengl_es".to_string(), glfw_use_opengl_es);
+    map.insert("glfw_use_opengl_core".to_string(), glfw_use_opengl_core);
+    map.insert("glfw_frame_delta".to_string(), glfw_frame_delta);
+    map.insert("glfw_set_window_icon".to_string(), glfw_set_window_icon);
+    map.insert("glfw_set_window_icon_multi".to_string(), glfw_set_window_icon_multi);
+    map.insert("glfw_set_id_strategy".to_string(), glfw_set_id_strategy);
+    map.insert("glfw_get_cursor_pos".to_string(), glfw_get_cursor_pos);
+    map.insert("glfw_get_cursor_pos_clamped".to_string(), glfw_get_cursor_pos_clamped);
+    map.insert("glfw_poll_events_grouped".to_string(), glfw_poll_events_grouped);
+    map.insert("glfw_match_video_mode".to_string(), glfw_match_video_mode);
+    map.insert("glfw_set_window_resizable".to_string(), glfw_set_window_resizable);
+    map.insert("glfw_get_error_log".to_string(), glfw_get_error_log);
+    map.insert("glfw_clear_error_log".to_string(), glfw_clear_error_log);
+    map.insert("glfw_get_gamepad_axis".to_string(), glfw_get_gamepad_axis);
+    map.insert("glfw_set_borderless_fullscreen".to_string(), glfw_set_borderless_fullscreen);
+    map.insert("glfw_set_cursor_hide_region".to_string(), glfw_set_cursor_hide_region);
+    map.insert("glfw_get_primary_content_scale".to_string(), glfw_get_primary_content_scale);
+    map.insert("glfw_fade_window".to_string(), glfw_fade_window);
+    map.insert("glfw_get_keys_pressed".to_string(), glfw_get_keys_pressed);
+    map.insert("glfw_destroy_window".to_string(), glfw_destroy_window);
+    map.insert("glfw_set_auto_destroy_closed".to_string(), glfw_set_auto_destroy_closed);
+    map.insert("glfw_fixed_timestep".to_string(), glfw_fixed_timestep);
+    map.insert("glfw_set_clipboard_bytes".to_string(), glfw_set_clipboard_bytes);
+    map.insert("glfw_get_clipboard_bytes".to_string(), glfw_get_clipboard_bytes);
+    map.insert("glfw_get_last_window".to_string(), glfw_get_last_window);
+    map.insert("glfw_get_focused_window".to_string(), glfw_get_focused_window);
+    map.insert("glfw_get_gamma_ramp".to_string(), glfw_get_gamma_ramp);
+    map.insert("glfw_get_cursor_velocity".to_string(), glfw_get_cursor_velocity);
+    map.insert("glfw_set_max_events_per_window".to_string(), glfw_set_max_events_per_window);
+    map.insert("glfw_get_clock_info".to_string(), glfw_get_clock_info);
+    map.insert("glfw_push_cursor_mode".to_string(), glfw_push_cursor_mode);
+    map.insert("glfw_pop_cursor_mode".to_string(), glfw_pop_cursor_mode);
+    map.insert("glfw_get_connected_joysticks".to_string(), glfw_get_connected_joysticks);
+    map.insert("glfw_set_window_pos_safe".to_string(), glfw_set_window_pos_safe);
+    map.insert("glfw_get_version".to_string(), glfw_get_version);
+    map.insert("glfw_get_version_compiled".to_string(), glfw_get_version_compiled);
+    map.insert("glfw_version_mismatch".to_string(), glfw_version_mismatch);
+    map.insert("glfw_set_fps_sample_window".to_string(), glfw_set_fps_sample_window);
+    map.insert("glfw_get_fps".to_string(), glfw_get_fps);
+    map.insert("glfw_set_event_key_names".to_string(), glfw_set_event_key_names);
+    map.insert("glfw_set_close_grace".to_string(), glfw_set_close_grace);
+    map.insert("glfw_confirm_close".to_string(), glfw_confirm_close);
+    map.insert("glfw_decode_mods".to_string(), glfw_decode_mods);
+    map.insert("glfw_make_context_current".to_string(), glfw_make_context_current);
+    map.insert("glfw_get_current_context".to_string(), glfw_get_current_context);
+    map.insert("glfw_event_type_code".to_string(), glfw_event_type_code);
+    map.insert("glfw_constants".to_string(), glfw_constants);
 }
 
-// --- IMPLEMENTATION ---
+// --- HELPERS ---
 
-fn glfw_init(_: Vec<Value>) -> Result<Value, String> {
-    let glfw = glfw::init(glfw::fail_on_errors)
-        .map_err(|e| format!("GLFW Init Error: {}", e))?;
+// Construit une map Aegis à partir de paires (clé, valeur)
+fn make_map(entries: Vec<(&str, Value)>) -> Value {
+    let map: HashMap<String, Value> = entries
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    Value::Map(Rc::new(RefCell::new(map)))
+}
 
-    let state = GlfwState {
-        context: glfw,
-        windows: HashMap::new(),
-        next_id: 1,
-    };
+// Construit un tableau Aegis
+fn make_array(items: Vec<Value>) -> Value {
+    Value::Array(Rc::new(RefCell::new(items)))
+}
 
-    let mut guard = STATE.lock().unwrap();
-    // On emballe dans le wrapper ThreadSafeState
-    *guard = Some(ThreadSafeState(state));
+// Lit un tableau Aegis (copie des éléments)
+fn value_as_array(value: &Value) -> Result<Vec<Value>, String> {
+    match value {
+        Value::Array(items) => Ok(items.borrow().clone()),
+        _ => Err("Expected an array".into()),
+    }
+}
 
-    println!("[Rust-GLFW] Initialized successfully.");
-    Ok(Value::Boolean(true))
+// Lit le champ `key` d'une map Aegis
+fn map_field(value: &Value, key: &str) -> Result<Value, String> {
+    match value {
+        Value::Map(map) => map.borrow().get(key).cloned().ok_or(format!("Missing field: {}", key)),
+        _ => Err("Expected a map".into()),
+    }
 }
 
-fn glfw_create_window(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 3 { return Err("Args: width, height, title".into()); }
-    
-    let width = args[0].as_int()? as u32;
-    let height = args[1].as_int()? as u32;
-    let title = args[2].as_str()?;
+// Lit un booléen Aegis
+fn value_as_bool(value: &Value) -> Result<bool, String> {
+    match value {
+        Value::Boolean(b) => Ok(*b),
+        _ => Err("Expected a boolean".into()),
+    }
+}
 
-    let mut guard = STATE.lock().unwrap();
-    // On accède au champ .0 du wrapper
-    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
-    let state = &mut state_wrapper.0;
+// Lit un nombre Aegis (Float ou Integer) en f64
+fn value_as_float(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Float(f) => Ok(*f),
+        Value::Integer(i) => Ok(*i as f64),
+        _ => Err("Expected a number".into()),
+    }
+}
 
-    let (mut window, events) = state.context.create_window(width, height, &title, glfw::WindowMode::Windowed)
-        .ok_or("Failed to create GLFW window")?;
+// Valide une dimension de fenêtre avant create_window (évite le "Failed to create" opaque)
+fn window_dimension(name: &str, value: i64) -> Result<u32, String> {
+    if value < 1 {
+        return Err(format!("Invalid window {}: {} (must be >= 1)", name, value));
+    }
+    if value > MAX_WINDOW_DIMENSION {
+        return Err(format!("Invalid window {}: {} (must be <= {})", name, value, MAX_WINDOW_DIMENSION));
+    }
+    Ok(value as u32)
+}
 
-    window.set_key_polling(true);
-    window.make_current();
+// glfw-rs passe les titres par CString::new, qui panique sur un NUL interne
+fn check_title(title: &str) -> Result<(), String> {
+    match title.find('\0') {
+        Some(pos) => Err(format!("Window title contains a NUL byte at index {}", pos)),
+        None => Ok(()),
+    }
+}
 
-    let id = state.next_id;
-    // Les types correspondent maintenant grâce à PWindow dans la struct
-    state.windows.insert(id, (window, events));
-    state.next_id += 1;
+// Convertit une dimension Aegis en limite GLFW (-1 = GLFW_DONT_CARE)
+fn size_limit(value: i64) -> Option<u32> {
+    if value < 0 { None } else { Some(value as u32) }
+}
 
-    println!("[Rust-GLFW] Window created with ID: {}", id);
-    Ok(Value::Integer(id as i64))
+// Lit un attribut de fenêtre brut (pour ceux que glfw-rs n'expose pas)
+fn window_attrib(window: &PWindow, attrib: std::os::raw::c_int) -> bool {
+    unsafe { glfw::ffi::glfwGetWindowAttrib(window.window_ptr(), attrib) == glfw::ffi::TRUE }
 }
 
-fn glfw_window_should_close(args: Vec<Value>) -> Result<Value, String> {
+// GLFW trop ancien, ou fenêtre sans framebuffer transparent : GLFW ignorerait l'opacité en silence
+fn opacity_supported(window: &PWindow) -> bool {
+    feature_supported("window_opacity").unwrap_or(false) && window_attrib(window, glfw::ffi::TRANSPARENT_FRAMEBUFFER)
+}
+
+// Erreur des setters d'opacité quand opacity_supported est faux
+fn require_opacity_support(window: &PWindow) -> Result<(), String> {
+    require_feature("window_opacity")?;
+    if !window_attrib(window, glfw::ffi::TRANSPARENT_FRAMEBUFFER) {
+        return Err("window opacity not supported on this platform/window".into());
+    }
+    Ok(())
+}
+
+// Construit un enregistrement d'événement { type, window, time, ... }
+fn event_record(kind: &str, window: usize, time: f64, fields: Vec<(&str, Value)>) -> Value {
+    let type_code = EVENT_TYPE_CODES.iter().find(|(name, _)| *name == kind).map_or(0, |(_, code)| *code);
+    let mut entries = vec![
+        ("type", Value::Str(kind.to_string())),
+        ("type_code", Value::Integer(type_code)),
+        ("window", Value::Integer(window as i64)),
+        ("time", Value::Float(time)),
+    ];
+    entries.extend(fields);
+    make_map(entries)
+}
+
+// Ajoute un enregistrement au tampon de poll avec son numéro de séquence.
+// Numéro croissant sur tous les polls, indépendant de l'horloge (survit à glfw_set_time).
+fn push_record(records: &mut Vec<Value>, next_seq: &mut u64, record: Value) {
+    if let Value::Map(map) = &record {
+        map.borrow_mut().insert("seq".to_string(), Value::Integer(*next_seq as i64));
+    }
+    *next_seq += 1;
+    records.push(record);
+}
+
+// Code entier d'une action : 0 = relâché, 1 = appuyé, 2 = répétition OS
+fn action_code(action: glfw::Action) -> i64 {
+    match action {
+        glfw::Action::Release => 0,
+        glfw::Action::Press => 1,
+        glfw::Action::Repeat => 2,
+    }
+}
+
+// Champ "mods" normalisé : seuls les bits de MOD_BITS sont conservés, quelle que soit la version
+fn mods_code(mods: glfw::Modifiers) -> i64 {
+    mods_mask(mods.bits() as i64)
+}
+
+// Ne garde que les bits de MOD_BITS (ceux d'une future version de GLFW sont écartés)
+fn mods_mask(bits: i64) -> i64 {
+    let known = MOD_BITS.iter().fold(0, |all, (_, bit)| all | bit);
+    bits & known
+}
+
+// Catégorie EVENT_* d'un WindowEvent
+fn event_category(event: &WindowEvent) -> i64 {
+    match event {
+        WindowEvent::Key(..) | WindowEvent::Char(..) | WindowEvent::CharModifiers(..) => EVENT_KEYS,
+        WindowEvent::MouseButton(..)
+        | WindowEvent::CursorPos(..)
+        | WindowEvent::CursorEnter(..)
+        | WindowEvent::Scroll(..) => EVENT_MOUSE,
+        WindowEvent::FileDrop(..) => EVENT_DROP,
+        _ => EVENT_WINDOW,
+    }
+}
+
+// Convertit un WindowEvent GLFW en enregistrement Aegis (None si non exposé)
+fn window_event_record(id: usize, time: f64, event: &WindowEvent) -> Option<Value> {
+    let record = match event {
+        WindowEvent::Key(key, scancode, action, mods) => event_record("key", id, time, vec![
+            ("key", Value::Integer(*key as i64)),
+            // Code matériel, indépendant de la disposition clavier
+            ("scancode", Value::Integer(*scancode as i64)),
+            ("action", Value::Integer(action_code(*action))),
+            ("mods", Value::Integer(mods_code(*mods))),
+        ]),
+        WindowEvent::Char(c) => event_record("char", id, time, vec![
+            ("codepoint", Value::Integer(*c as i64)),
+        ]),
+        WindowEvent::MouseButton(button, action, mods) => event_record("mouse_button", id, time, vec![
+            ("button", Value::Integer(*button as i64)),
+            ("action", Value::Integer(action_code(*action))),
+            ("mods", Value::Integer(mods_code(*mods))),
+        ]),
+        WindowEvent::CursorPos(x, y) => event_record("cursor_pos", id, time, vec![
+            ("x", Value::Float(*x)),
+            ("y", Value::Float(*y)),
+        ]),
+        WindowEvent::Scroll(x, y) => event_record("scroll", id, time, vec![
+            ("x", Value::Float(*x)),
+            ("y", Value::Float(*y)),
+        ]),
+        WindowEvent::Pos(x, y) => event_record("pos", id, time, vec![
+            ("x", Value::Integer(*x as i64)),
+            ("y", Value::Integer(*y as i64)),
+        ]),
+        WindowEvent::Size(w, h) => event_record("size", id, time, vec![
+            ("width", Value::Integer(*w as i64)),
+            ("height", Value::Integer(*h as i64)),
+        ]),
+        // En pixels : diffère de "size" (coordonnées écran) sur les écrans HiDPI
+        WindowEvent::FramebufferSize(w, h) => event_record("framebuffer_size", id, time, vec![
+            ("width", Value::Integer(*w as i64)),
+            ("height", Value::Integer(*h as i64)),
+        ]),
+        WindowEvent::Close => event_record("close", id, time, vec![]),
+        WindowEvent::Focus(focused) => event_record("focus", id, time, vec![
+            ("focused", Value::Boolean(*focused)),
+        ]),
+        WindowEvent::Iconify(iconified) => event_record("iconify", id, time, vec![
+            ("iconified", Value::Boolean(*iconified)),
+        ]),
+        // Maximisation par l'utilisateur comme par glfw (maximize / restore)
+        WindowEvent::Maximize(maximized) => event_record("maximize", id, time, vec![
+            ("maximized", Value::Boolean(*maximized)),
+        ]),
+        _ => return None,
+    };
+    Some(record)
+}
+
+// Indique si la version de GLFW chargée à l'exécution supporte la fonctionnalité
+// (None si le nom est inconnu de la table)
+fn feature_supported(name: &str) -> Option<bool> {
+    let version = glfw::get_version();
+    feature_supported_by(name, (version.major, version.minor))
+}
+
+// Idem pour une version (majeure, mineure) donnée
+fn feature_supported_by(name: &str, version: (u64, u64)) -> Option<bool> {
+    let (_, required) = FEATURE_VERSIONS.iter().find(|(feature, _)| *feature == name)?;
+    Some(version >= *required)
+}
+
+// Un runtime trop ancien renverrait une valeur indéfinie (ou une erreur GLFW) pour un attribut
+// plus récent que lui : on refuse clairement
+fn check_attrib_version(name: &str, version: (u64, u64)) -> Result<(), String> {
+    if let Some((_, feature)) = ATTRIB_FEATURES.iter().find(|(n, _)| *n == name) {
+        if !feature_supported_by(feature, version).unwrap_or(false) {
+            return Err(format!("Window attrib {} unsupported on this GLFW version ({}.{})", name, version.0, version.1));
+        }
+    }
+    Ok(())
+}
+
+// Mode vidéo courant du moniteur principal (None si aucun moniteur)
+fn primary_video_mode(glfw: &mut Glfw) -> Option<glfw::VidMode> {
+    glfw.with_primary_monitor(|_, monitor| monitor.and_then(|m| m.get_video_mode()))
+}
+
+// Convertit un code entier en glfw::Key, uniquement s'il figure dans KEY_CODES
+fn key_from_code(code: i64) -> Option<glfw::Key> {
+    let code = code as i32;
+    KEY_CODES.iter().find(|(_, c)| *c == code)?;
+    // Safe : le code est une discriminante valide de l'enum glfw::Key (repr i32)
+    Some(unsafe { std::mem::transmute::<i32, glfw::Key>(code) })
+}
+
+// Convertit un code entier (0..7) en glfw::MouseButton
+fn mouse_button_from_code(code: i64) -> Option<glfw::MouseButton> {
+    use glfw::MouseButton::*;
+    Some(match code {
+        0 => Button1,
+        1 => Button2,
+        2 => Button3,
+        3 => Button4,
+        4 => Button5,
+        5 => Button6,
+        6 => Button7,
+        7 => Button8,
+        _ => return None,
+    })
+}
+
+// Lit une transition de touche du dernier poll (pressed = true : appui, sinon relâchement)
+fn key_edge(args: Vec<Value>, pressed: bool) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, key_code".into());
+    }
+
     let id = args[0].as_int()? as usize;
+    let code = args[1].as_int()?;
+    let key = key_from_code(code).ok_or(format!("Unknown key code: {}", code))?;
+
     let mut guard = STATE.lock().unwrap();
     let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
     let state = &mut state_wrapper.0;
-    
-    if let Some((window, _)) = state.windows.get(&id) {
-        return Ok(Value::Boolean(window.should_close()));
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
     }
-    Ok(Value::Boolean(true))
+
+    let edge = state.frame_input.key_edges.get(&id).is_some_and(|edges| {
+        if pressed { edges.pressed.contains(&(key as i32)) } else { edges.released.contains(&(key as i32)) }
+    });
+    Ok(Value::Boolean(edge))
 }
 
-fn glfw_swap_buffers(args: Vec<Value>) -> Result<Value, String> {
+// Lit une transition de bouton de souris du dernier poll
+fn mouse_button_edge(args: Vec<Value>, pressed: bool) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, button".into());
+    }
+
     let id = args[0].as_int()? as usize;
+    let code = args[1].as_int()?;
+    let button = mouse_button_from_code(code).ok_or(format!("Unknown mouse button: {}", code))?;
+
     let mut guard = STATE.lock().unwrap();
     let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
     let state = &mut state_wrapper.0;
 
-    if let Some((window, _)) = state.windows.get_mut(&id) {
-        window.swap_buffers();
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
     }
-    Ok(Value::Null)
+
+    let edge = state.frame_input.mouse_button_edges.get(&id).is_some_and(|edges| {
+        if pressed { edges.pressed.contains(&(button as i32)) } else { edges.released.contains(&(button as i32)) }
+    });
+    Ok(Value::Boolean(edge))
 }
 
-fn glfw_poll_events(_: Vec<Value>) -> Result<Value, String> {
-    let mut guard = STATE.lock().unwrap();
-    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
-    let state = &mut state_wrapper.0;
-    
-    state.context.poll_events();
-    Ok(Value::Null)
+// Nom (sans préfixe) d'un code de window hint / attribut
+fn window_hint_name(code: i64) -> Option<&'static str> {
+    WINDOW_HINT_CODES.iter().find(|(_, c)| *c == code).map(|(name, _)| *name)
 }
 
-fn glfw_get_proc_address(_: Vec<Value>) -> Result<Value, String> {
-    let ptr = glfw::ffi::glfwGetProcAddress as usize;
-    Ok(Value::Integer(ptr as i64))
+// Lit un hint booléen (Boolean, ou Integer 0/1 comme en C)
+fn hint_bool(value: &Value) -> Result<bool, String> {
+    match value {
+        Value::Integer(i) => Ok(*i != 0),
+        other => value_as_bool(other),
+    }
 }
 
-fn glfw_get_key(args: Vec<Value>) -> Result<Value, String> {
+// Lit un hint entier (-1 = GLFW_DONT_CARE)
+fn hint_uint(value: &Value) -> Result<Option<u32>, String> {
+    let value = value.as_int()?;
+    Ok(if value < 0 { None } else { Some(value as u32) })
+}
+
+// Passe un hint directement à GLFW (hints 3.4 non exposés par glfw-rs)
+fn raw_window_hint(code: i64, value: &Value) -> Result<(), String> {
+    let value = match value {
+        Value::Boolean(b) => *b as i64,
+        other => other.as_int()?,
+    };
+    unsafe { glfw::ffi::glfwWindowHint(code as std::os::raw::c_int, value as std::os::raw::c_int) };
+    Ok(())
+}
+
+// Applique un window hint avant glfw_create_window
+fn apply_window_hint(glfw: &mut Glfw, code: i64, value: &Value) -> Result<(), String> {
+    use glfw::WindowHint;
+    let name = window_hint_name(code).ok_or(format!("Unknown window hint: {:#x}", code))?;
+
+    let hint = match name {
+        "RESIZABLE" => WindowHint::Resizable(hint_bool(value)?),
+        "VISIBLE" => WindowHint::Visible(hint_bool(value)?),
+        "DECORATED" => WindowHint::Decorated(hint_bool(value)?),
+        "FOCUSED" => WindowHint::Focused(hint_bool(value)?),
+        "AUTO_ICONIFY" => WindowHint::AutoIconify(hint_bool(value)?),
+        "FLOATING" => WindowHint::Floating(hint_bool(value)?),
+        "MAXIMIZED" => WindowHint::Maximized(hint_bool(value)?),
+        "TRANSPARENT_FRAMEBUFFER" => WindowHint::TransparentFramebuffer(hint_bool(value)?),
+        "SAMPLES" => WindowHint::Samples(hint_uint(value)?),
+        "DOUBLEBUFFER" => WindowHint::DoubleBuffer(hint_bool(value)?),
+        "CONTEXT_VERSION_MAJOR" => WindowHint::ContextVersionMajor(value.as_int()? as u32),
+        "CONTEXT_VERSION_MINOR" => WindowHint::ContextVersionMinor(value.as_int()? as u32),
+        "OPENGL_FORWARD_COMPAT" => WindowHint::OpenGlForwardCompat(hint_bool(value)?),
+        "CLIENT_API" => WindowHint::ClientApi(match value.as_int()? {
+            0 => glfw::ClientApiHint::NoApi,
+            0x00030001 => glfw::ClientApiHint::OpenGl,
+            0x00030002 => glfw::ClientApiHint::OpenGlEs,
+            other => return Err(format!("Invalid CLIENT_API value: {:#x}", other)),
+        }),
+        "OPENGL_PROFILE" => WindowHint::OpenGlProfile(match value.as_int()? {
+            0 => glfw::OpenGlProfileHint::Any,
+            0x00032001 => glfw::OpenGlProfileHint::Core,
+            0x00032002 => glfw::OpenGlProfileHint::Compat,
+            other => return Err(format!("Invalid OPENGL_PROFILE value: {:#x}", other)),
+        }),
+        "CONTEXT_NO_ERROR" => WindowHint::ContextNoError(hint_bool(value)?),
+        "CONTEXT_RELEASE_BEHAVIOR" => WindowHint::ContextReleaseBehavior(match value.as_int()? {
+            0 => glfw::ContextReleaseBehavior::Any,
+            0x00035001 => glfw::ContextReleaseBehavior::Flush,
+            0x00035002 => glfw::ContextReleaseBehavior::None,
+            other => return Err(format!("Invalid CONTEXT_RELEASE_BEHAVIOR value: {:#x}", other)),
+        }),
+        "CONTEXT_CREATION_API" => WindowHint::ContextCreationApi(match value.as_int()? {
+            0x00036001 => glfw::ContextCreationApi::Native,
+            0x00036002 => glfw::ContextCreationApi::Egl,
+            0x00036003 => glfw::ContextCreationApi::OsMesa,
+            other => return Err(format!("Invalid CONTEXT_CREATION_API value: {:#x}", other)),
+        }),
+        "SCALE_TO_MONITOR" => WindowHint::ScaleToMonitor(hint_bool(value)?),
+        "SCALE_FRAMEBUFFER" => {
+            if feature_supported("scale_framebuffer").unwrap_or(false) {
+                return raw_window_hint(code, value);
+            }
+            // Avant GLFW 3.4, le même réglage s'appelait COCOA_RETINA_FRAMEBUFFER
+            WindowHint::CocoaRetinaFramebuffer(hint_bool(value)?)
+        }
+        "MOUSE_PASSTHROUGH" => {
+            require_feature("mouse_passthrough")?;
+            return raw_window_hint(code, value);
+        }
+        "FOCUS_ON_SHOW" => {
+            require_feature("focus_on_show")?;
+            return raw_window_hint(code, value);
+        }
+        "POSITION_X" | "POSITION_Y" => {
+            // Avant 3.4, glfw_window_hint mémorise la position et create_window la simule
+            if feature_supported("position_hint").unwrap_or(false) {
+                return raw_window_hint(code, value);
+            }
+            return Ok(());
+        }
+        _ => return Err(format!("Window hint {} cannot be set", name)),
+    };
+
+    glfw.window_hint(hint);
+    Ok(())
+}
+
+// Lit un hint POSITION_X / POSITION_Y (None = GLFW_ANY_POSITION)
+fn position_hint_value(value: &Value) -> Result<Option<i32>, String> {
+    let value = value.as_int()?;
+    if value == ANY_POSITION {
+        return Ok(None);
+    }
+    i32::try_from(value).map(Some).map_err(|_| format!("Invalid window position: {}", value))
+}
+
+// Version de GLFW des en-têtes utilisés par glfw-rs à la compilation (major, minor, revision)
+fn compiled_version() -> (u64, u64, u64) {
+    (glfw::ffi::VERSION_MAJOR as u64, glfw::ffi::VERSION_MINOR as u64, glfw::ffi::VERSION_REVISION as u64)
+}
+
+fn version_map(major: u64, minor: u64, revision: u64) -> Value {
+    make_map(vec![
+        ("major", Value::Integer(major as i64)),
+        ("minor", Value::Integer(minor as i64)),
+        ("revision", Value::Integer(revision as i64)),
+    ])
+}
+
+// Erreur explicite si la version de GLFW chargée ne connaît pas la fonctionnalité
+fn require_feature(feature: &str) -> Result<(), String> {
+    if feature_supported(feature).unwrap_or(false) {
+        return Ok(());
+    }
+    let version = glfw::get_version();
+    Err(format!("{} is not supported by this GLFW version ({}.{})", feature, version.major, version.minor))
+}
+
+// Callback d'erreur GLFW installé à l'init (remplace fail_on_errors).
+// Il peut être appelé pendant que STATE est verrouillé : on ne touche qu'aux files dédiées.
+fn record_glfw_error(error: glfw::Error, description: String) {
+    let code = error as i32;
+    *LAST_ERROR.lock().unwrap() = Some((code, description.clone()));
+
+    let mut log = ERROR_LOG.lock().unwrap();
+    if log.len() == ERROR_LOG_SIZE {
+        log.pop_front();
+    }
+    log.push_back((code, description.clone()));
+    drop(log);
+
+    PENDING_ERRORS.lock().unwrap().push((code, description));
+}
+
+// Codes d'erreur GLFW utiles au diagnostic de glfwInit
+const ERROR_API_UNAVAILABLE: i32 = 0x00010006;
+const ERROR_PLATFORM_ERROR: i32 = 0x00010008;
+const ERROR_PLATFORM_UNAVAILABLE: i32 = 0x0001000E;
+
+// Message d'échec de glfw_init, à partir de la dernière erreur remontée par le callback
+fn init_failure_message(fallback: &str) -> String {
+    let Some((code, description)) = LAST_ERROR.lock().unwrap().clone() else {
+        return format!("GLFW Init Error: {}", fallback);
+    };
+
+    // X11/Wayland : pas de $DISPLAY / compositeur (SSH, CI sans Xvfb...)
+    let lower = description.to_lowercase();
+    let reason = if lower.contains("display") || lower.contains("wayland: failed to connect") {
+        "no display available"
+    } else {
+        match code {
+            ERROR_PLATFORM_UNAVAILABLE => "platform unavailable",
+            ERROR_API_UNAVAILABLE => "graphics API unavailable",
+            ERROR_PLATFORM_ERROR => "platform error",
+            _ => "initialization failed",
+        }
+    };
+    format!("GLFW Init Error: {} ({})", reason, description)
+}
+
+// Transmet les erreurs GLFW en attente au callback Aegis (s'il y en a un)
+fn flush_errors(state: &mut GlfwState) {
+    let errors: Vec<(i32, String)> = PENDING_ERRORS.lock().unwrap().drain(..).collect();
+    if let Some(callback) = &state.error_callback {
+        for (code, description) in errors {
+            queue_callback(&mut state.callback_queue, callback, vec![
+                Value::Integer(code as i64),
+                Value::Str(description),
+            ]);
+        }
+    }
+}
+
+// Met en file un appel de callback Aegis : [fonction, nb_args, args...]
+// Les natives ne peuvent pas appeler l'interpréteur : c'est glfw.aeg qui dépile
+// via glfw_next_callback et appelle la fonction, STATE n'étant alors plus verrouillé.
+fn queue_callback(queue: &mut VecDeque<Value>, callback: &Value, args: Vec<Value>) {
+    let mut call = vec![callback.clone(), Value::Integer(args.len() as i64)];
+    call.extend(args);
+    queue.push_back(make_array(call));
+}
+
+// Lit un callback Aegis optionnel (Null = suppression)
+fn optional_callback(value: &Value) -> Option<Value> {
+    match value {
+        Value::Null => None,
+        callback => Some(callback.clone()),
+    }
+}
+
+// Enregistre (ou efface avec Null) un callback de fenêtre : args = [win_id, callback]
+fn set_window_callback(args: Vec<Value>, slot: fn(&mut WindowCallbacks) -> &mut Option<Value>) -> Result<Value, String> {
     if args.len() != 2 {
-        return Err("Args: win_id, key_code".into());
+        return Err("Args: win_id, callback (or null)".into());
     }
 
     let id = args[0].as_int()? as usize;
-    let key_code = args[1].as_int()? as i32;
-
     let mut guard = STATE.lock().unwrap();
     let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
     let state = &mut state_wrapper.0;
 
-    if let Some((window, _)) = state.windows.get(&id) {
-        // Convert raw int to GLFW Key enum (unsafe but necessary for raw binding)
-        // Or simpler: use glfw::Key::from_i32 if available, or just map manually.
-        // For simplicity in a dynamic binding, we trust the integer passed matches GLFW constants.
-        // Note: glfw-rs expects a Key enum. We need a way to cast int to Key.
-        // Since we can't easily cast int to Enum in safe Rust without a huge match,
-        // let's assume the user passes the correct ID.
-        
-        // Hack: Transmute int to Key (works because Key is repr(i32) usually)
-        // A cleaner way would be a huge match statement, but for a binding engine:
-        let key: glfw::Key = unsafe { std::mem::transmute(key_code) };
-        
-        let action = window.get_key(key);
-        // Returns true if Press or Repeat
-        return Ok(Value::Boolean(action == glfw::Action::Press || action == glfw::Action::Repeat));
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
     }
 
-    Ok(Value::Boolean(false))
+    let callbacks = state.window_callbacks.entry(id).or_default();
+    *slot(callbacks) = optional_callback(&args[1]);
+    Ok(Value::Null)
 }
 
-fn glfw_get_time(_: Vec<Value>) -> Result<Value, String> {
-    let mut guard = STATE.lock().unwrap();
-    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
-    let state = &mut state_wrapper.0;
+// Alphabet base64 standard (RFC 4648), avec remplissage '='
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Taille maximale d'un blob binaire passé par le presse-papiers (avant encodage)
+const MAX_CLIPBOARD_BYTES: usize = 1 << 20;
 
-    Ok(Value::Float(state.context.get_time()))
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// None si le texte n'est pas du base64 valide (les blancs autour sont tolérés)
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim().as_bytes();
+    if text.len() % 4 != 0 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    for (index, chunk) in text.chunks(4).enumerate() {
+        let last = index == text.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+        let mut n = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let digit = BASE64_ALPHABET.iter().position(|a| *a == c)? as u32;
+            n = n << 6 | digit;
+        }
+        n <<= 6 * padding as u32;
+        out.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8][..3 - padding]);
+    }
+    Some(out)
+}
+
+// Lit le presse-papiers sans fenêtre (GLFW accepte une fenêtre NULL)
+fn read_global_clipboard() -> Option<String> {
+    read_clipboard(std::ptr::null_mut(), None)
+}
+
+// Lit le presse-papiers en ne copiant au plus que `limit` octets.
+// La chaîne reste possédée par GLFW : seule la partie gardée est allouée côté Rust.
+fn read_clipboard(window: *mut glfw::ffi::GLFWwindow, limit: Option<usize>) -> Option<String> {
+    let bytes = unsafe {
+        let ptr = glfw::ffi::glfwGetClipboardString(window);
+        if ptr.is_null() {
+            return None;
+        }
+        std::ffi::CStr::from_ptr(ptr).to_bytes()
+    };
+
+    let kept = limit.map_or(bytes, |limit| truncate_at_char_boundary(bytes, limit));
+    Some(String::from_utf8_lossy(kept).into_owned())
+}
+
+// Au plus `limit` octets, sans couper au milieu d'un caractère UTF-8 (octets de continuation 10xxxxxx)
+fn truncate_at_char_boundary(bytes: &[u8], limit: usize) -> &[u8] {
+    let mut end = limit.min(bytes.len());
+    while end < bytes.len() && end > 0 && bytes[end] & 0xC0 == 0x80 {
+        end -= 1;
+    }
+    &bytes[..end]
+}
+
+// Convertit un code CURSOR_* en glfw::CursorMode
+// Les codes acceptés sont ceux de CURSOR_MODE_CODES
+fn cursor_mode_from_code(code: i64) -> Result<glfw::CursorMode, String> {
+    match code {
+        CURSOR_NORMAL => Ok(glfw::CursorMode::Normal),
+        CURSOR_HIDDEN => Ok(glfw::CursorMode::Hidden),
+        CURSOR_DISABLED => Ok(glfw::CursorMode::Disabled),
+        _ => Err(format!("Unknown cursor mode: {:#x}", code)),
+    }
+}
+
+// Rectangles (x, y, w, h) d'un moniteur : zone complète et zone de travail
+struct MonitorRects {
+    id: usize,
+    area: (i32, i32, i32, i32),
+    workarea: (i32, i32, i32, i32),
+}
+
+// Géométrie de tous les moniteurs du registre
+fn monitor_rects(state: &mut GlfwState) -> Vec<MonitorRects> {
+    let registry: Vec<(usize, *mut glfw::ffi::GLFWmonitor)> =
+        state.monitors.iter().map(|(id, ptr)| (*id, *ptr)).collect();
+
+    state.context.with_connected_monitors(|_, monitors| {
+        registry.iter().filter_map(|(id, ptr)| {
+            let monitor = monitors.iter().find(|m| m.as_ptr() == *ptr)?;
+            let (x, y) = monitor.get_pos();
+            let (w, h) = monitor.get_video_mode()
+                .map(|mode| (mode.width as i32, mode.height as i32))
+                .unwrap_or((0, 0));
+            Some(MonitorRects { id: *id, area: (x, y, w, h), workarea: monitor.get_workarea() })
+        }).collect()
+    })
+}
+
+// Surface commune de deux rectangles (x, y, w, h)
+fn overlap_area(a: (i32, i32, i32, i32), b: (i32, i32, i32, i32)) -> i64 {
+    let w = (a.0 + a.2).min(b.0 + b.2) - a.0.max(b.0);
+    let h = (a.1 + a.3).min(b.1 + b.3) - a.1.max(b.1);
+    if w <= 0 || h <= 0 { 0 } else { w as i64 * h as i64 }
+}
+
+// Moniteur "courant" d'une fenêtre : celui qu'elle recouvre le plus
+// (GLFW ne connaît le moniteur que des fenêtres plein écran)
+fn monitor_for_window(state: &mut GlfwState, id: usize) -> Result<Option<usize>, String> {
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    let (x, y) = window.get_pos();
+    let (w, h) = window.get_size();
+
+    let best = monitor_rects(state)
+        .into_iter()
+        .map(|rects| (overlap_area((x, y, w, h), rects.area), rects.id))
+        .filter(|(overlap, _)| *overlap > 0)
+        .max();
+    Ok(best.map(|(_, monitor_id)| monitor_id))
+}
+
+// Id du moniteur principal dans le registre (None si aucun moniteur)
+fn primary_monitor_id(state: &mut GlfwState) -> Option<usize> {
+    let ptr = state.context.with_primary_monitor(|_, monitor| monitor.map(|m| m.as_ptr()))?;
+    state.monitors.iter().find(|(_, p)| **p == ptr).map(|(id, _)| *id)
+}
+
+// Exécute f sur le moniteur enregistré sous cet id
+fn with_monitor<T>(state: &mut GlfwState, id: usize, f: impl FnOnce(&glfw::Monitor) -> T) -> Result<T, String> {
+    let ptr = *state.monitors.get(&id).ok_or(format!("Unknown monitor id: {}", id))?;
+    state.context
+        .with_connected_monitors(|_, monitors| monitors.iter().find(|m| m.as_ptr() == ptr).map(f))
+        .ok_or(format!("Unknown monitor id: {}", id))
+}
+
+// Convertit une map { width, height, pixels } en image GLFW.
+// pixels : tableau de width * height * 4 octets RGBA, ligne par ligne depuis le haut.
+fn pixel_image(value: &Value) -> Result<glfw::PixelImage, String> {
+    let width = map_field(value, "width")?.as_int()?;
+    let height = map_field(value, "height")?.as_int()?;
+    if width <= 0 || height <= 0 || width > MAX_WINDOW_DIMENSION || height > MAX_WINDOW_DIMENSION {
+        return Err(format!("Invalid image size: {}x{}", width, height));
+    }
+
+    let bytes = value_as_array(&map_field(value, "pixels")?)?;
+    let expected = (width * height * 4) as usize;
+    if bytes.len() != expected {
+        return Err(format!("Image {}x{} needs {} pixel bytes (RGBA), got {}", width, height, expected, bytes.len()));
+    }
+
+    let mut pixels = Vec::with_capacity(expected / 4);
+    for rgba in bytes.chunks_exact(4) {
+        let mut channels = [0u8; 4];
+        for (channel, byte) in channels.iter_mut().zip(rgba) {
+            let byte = byte.as_int()?;
+            *channel = u8::try_from(byte).map_err(|_| format!("Invalid pixel byte: {}", byte))?;
+        }
+        // GLFW lit les octets en mémoire dans l'ordre R, G, B, A
+        pixels.push(u32::from_ne_bytes(channels));
+    }
+    Ok(glfw::PixelImage { width: width as u32, height: height as u32, pixels })
+}
+
+// Enregistrement Aegis d'un mode vidéo
+fn video_mode_record(mode: &glfw::VidMode) -> Value {
+    make_map(vec![
+        ("width", Value::Integer(mode.width as i64)),
+        ("height", Value::Integer(mode.height as i64)),
+        ("refresh_rate", Value::Integer(mode.refresh_rate as i64)),
+        ("red_bits", Value::Integer(mode.red_bits as i64)),
+        ("green_bits", Value::Integer(mode.green_bits as i64)),
+        ("blue_bits", Value::Integer(mode.blue_bits as i64)),
+    ])
+}
+
+// Convertit un entier Aegis (0..15) en JoystickId GLFW
+fn joystick_id(jid: i64) -> Result<glfw::JoystickId, String> {
+    glfw::JoystickId::from_i32(jid as i32).ok_or(format!("Invalid joystick id: {}", jid))
+}
+
+// --- IMPLEMENTATION ---
+
+// Zone morte appliquée axe par axe : 0 sous le seuil, puis remise à l'échelle sur [seuil, 1]
+// pour que la sortie reparte de 0 sans saut
+fn apply_deadzone(value: f64, deadzone: f64) -> f64 {
+    let magnitude = value.abs();
+    if magnitude < deadzone {
+        return 0.0;
+    }
+    (value.signum() * (magnitude - deadzone) / (1.0 - deadzone)).clamp(-1.0, 1.0)
+}
+
+fn glfw_init(_: Vec<Value>) -> Result<Value, String> {
+    // Pas de fail_on_errors : une erreur GLFW ne doit pas tuer le programme Aegis
+    *LAST_ERROR.lock().unwrap() = None;
+    // Erreurs levées hors session (appels non gardés après terminate) : elles ne concernent pas celle-ci
+    PENDING_ERRORS.lock().unwrap().clear();
+    let mut glfw = glfw::init(record_glfw_error).map_err(|e| init_failure_message(&e.to_string()))?;
+
+    // Enregistré dès l'init (et non à la création d'une fenêtre) : il initialise aussi la détection
+    // des manettes côté GLFW, ce qui permet d'utiliser poll sans fenêtre
+    glfw.set_joystick_callback(|jid: glfw::JoystickId, event: glfw::JoystickEvent| {
+        let connected = matches!(event, glfw::JoystickEvent::Connected);
+        JOYSTICK_EVENTS.lock().unwrap().push((jid as i32, connected));
+    });
+
+    glfw.set_monitor_callback(|monitor: glfw::Monitor, event: glfw::MonitorEvent| {
+        let connected = matches!(event, glfw::MonitorEvent::Connected);
+        MONITOR_EVENTS.lock().unwrap().push((monitor.as_ptr() as usize, connected));
+    });
+
+    // Les moniteurs déjà branchés reçoivent les ids 1..n
+    let monitor_ptrs: Vec<*mut glfw::ffi::GLFWmonitor> =
+        glfw.with_connected_monitors(|_, monitors| monitors.iter().map(|m| m.as_ptr()).collect());
+    let monitors: HashMap<usize, *mut glfw::ffi::GLFWmonitor> =
+        monitor_ptrs.into_iter().enumerate().map(|(i, ptr)| (i + 1, ptr)).collect();
+    let next_monitor_id = monitors.len() + 1;
+
+    let last_video_mode = primary_video_mode(&mut glfw);
+    let monotonic = MONOTONIC_WINDOW_IDS.load(Ordering::Relaxed);
+    let next_id = if monotonic { LAST_WINDOW_ID.load(Ordering::Relaxed) + 1 } else { 1 };
+    let next_event_seq = if monotonic { LAST_EVENT_SEQ.load(Ordering::Relaxed) + 1 } else { 1 };
+    let init_timer_value = glfw.get_timer_value();
+    let state = GlfwState {
+        context: glfw,
+        init_thread: std::thread::current().id(),
+        windows: HashMap::new(),
+        next_id,
+        restored_geometry: HashMap::new(),
+        window_tags: HashMap::new(),
+        last_video_mode,
+        frame_input: FrameInput::default(),
+        input: InputTracking::default(),
+        auto_poll: false,
+        pending_events: Vec::new(),
+        size_limits: HashMap::new(),
+        close_times: HashMap::new(),
+        event_filter: EVENT_ALL,
+        monitors,
+        next_monitor_id,
+        monitor_callback: None,
+        callback_queue: VecDeque::new(),
+        error_callback: None,
+        last_drained: 0,
+        window_sizes: HashMap::new(),
+        window_callbacks: HashMap::new(),
+        event_queue: VecDeque::new(),
+        time_paused_at: None,
+        paused_duration: 0.0,
+        windowed_geometry: HashMap::new(),
+        clipboard_hash: None,
+        last_poll_duration: 0.0,
+        cursor_modes: HashMap::new(),
+        last_resize_times: HashMap::new(),
+        event_buffer: Vec::new(),
+        context_fallback: false,
+        fps_counters: HashMap::new(),
+        attention_pending: HashSet::new(),
+        auto_clear_attention: false,
+        dpi_autoscale: HashMap::new(),
+        aspect_ratios: HashMap::new(),
+        window_titles: HashMap::new(),
+        ignore_next_close: HashSet::new(),
+        clipboard_read_limit: None,
+        last_frame_time: None,
+        resizable: HashMap::new(),
+        position_hint: (None, None),
+        visible_hint: true,
+        next_event_seq,
+        cursor_hide_regions: HashMap::new(),
+        fades: HashMap::new(),
+        auto_destroy_closed: false,
+        fixed_last_time: None,
+        fixed_accumulator: 0.0,
+        focused_window: 0,
+        max_events_per_window: None,
+        events_deferred: false,
+        init_timer_value,
+        cursor_mode_stacks: HashMap::new(),
+        fps_sample_window: FPS_SAMPLES,
+        event_key_names: false,
+        close_graces: HashMap::new(),
+        close_requests: HashMap::new(),
+        current_context: 0,
+    };
+
+    let mut guard = STATE.lock().unwrap();
+    // On emballe dans le wrapper ThreadSafeState
+    *guard = Some(ThreadSafeState(state));
+    GLFW_INITIALIZED.store(true, Ordering::Release);
+
+    println!("[Rust-GLFW] Initialized successfully.");
+    Ok(Value::Boolean(true))
+}
+
+fn glfw_create_window(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 { return Err("Args: width, height, title".into()); }
+    
+    let width = window_dimension("width", args[0].as_int()?)?;
+    let height = window_dimension("height", args[1].as_int()?)?;
+    let title = args[2].as_str()?;
+
+    let mut guard = STATE.lock().unwrap();
+    // On accède au champ .0 du wrapper
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let id = create_window(state, width, height, &title)?;
+    Ok(Value::Integer(id as i64))
+}
+
+// Crée une fenêtre (avec repli de contexte si activé), l'enregistre et renvoie son id
+fn create_window(state: &mut GlfwState, width: u32, height: u32, title: &str) -> Result<usize, String> {
+    check_title(title)?;
+
+    // Avant GLFW 3.4 : création cachée, déplacement puis affichage, sans saut visible
+    let emulate_position = !feature_supported("position_hint").unwrap_or(false)
+        && (state.position_hint.0.is_some() || state.position_hint.1.is_some());
+    if emulate_position {
+        state.context.window_hint(glfw::WindowHint::Visible(false));
+    }
+
+    let mut created = state.context.create_window(width, height, title, glfw::WindowMode::Windowed);
+    flush_errors(state);
+
+    if state.context_fallback {
+        // Les hints de repli restent en place pour les fenêtres suivantes : même contexte partout
+        for &(api, major, minor, profile) in CONTEXT_FALLBACKS {
+            if created.is_some() {
+                break;
+            }
+            state.context.window_hint(glfw::WindowHint::ClientApi(api));
+            state.context.window_hint(glfw::WindowHint::ContextVersion(major, minor));
+            state.context.window_hint(glfw::WindowHint::OpenGlProfile(profile));
+            // Forward-compat n'existe qu'à partir de 3.0 (et pas en ES)
+            state.context.window_hint(glfw::WindowHint::OpenGlForwardCompat(major >= 3 && matches!(api, glfw::ClientApiHint::OpenGl)));
+            created = state.context.create_window(width, height, title, glfw::WindowMode::Windowed);
+            flush_errors(state);
+        }
+    }
+
+    if emulate_position {
+        state.context.window_hint(glfw::WindowHint::Visible(state.visible_hint));
+    }
+
+    let (mut window, events) = created.ok_or_else(|| match LAST_ERROR.lock().unwrap().as_ref() {
+        Some((_, description)) => format!("Failed to create GLFW window: {}", description),
+        None => "Failed to create GLFW window".to_string(),
+    })?;
+
+    if emulate_position {
+        let (x, y) = window.get_pos();
+        window.set_pos(state.position_hint.0.unwrap_or(x), state.position_hint.1.unwrap_or(y));
+        if state.visible_hint {
+            window.show();
+        }
+    }
+
+    window.set_key_polling(true);
+    window.set_char_polling(true);
+    window.set_mouse_button_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+    window.set_pos_polling(true);
+    window.set_size_polling(true);
+    window.set_framebuffer_size_polling(true);
+    if feature_supported("content_scale").unwrap_or(false) {
+        window.set_content_scale_polling(true);
+    }
+    window.set_close_polling(true);
+    window.set_focus_polling(true);
+    window.set_iconify_polling(true);
+    if feature_supported("maximize_event").unwrap_or(false) {
+        window.set_maximize_polling(true);
+    }
+    // La nouvelle fenêtre prend le contexte courant (celui d'une autre fenêtre est détaché) :
+    // glfw_get_current_context permet de le constater. Une fenêtre NO_API n'en a pas,
+    // GLFW lèverait GLFW_NO_WINDOW_CONTEXT : le contexte courant reste inchangé.
+    let has_context = window.get_client_api() != glfw::ffi::NO_API;
+    if has_context {
+        window.make_current();
+    }
+
+    let id = state.next_id;
+    if has_context {
+        state.current_context = id;
+    }
+    let (x, y) = window.get_pos();
+    let (w, h) = window.get_size();
+    state.restored_geometry.insert(id, (x, y, w, h));
+    state.window_sizes.insert(id, (w, h));
+    state.cursor_modes.insert(id, CURSOR_NORMAL);
+    state.window_titles.insert(id, title.to_string());
+    state.resizable.insert(id, window.is_resizable());
+    // Les types correspondent maintenant grâce à PWindow dans la struct
+    state.windows.insert(id, (window, events));
+    state.next_id += 1;
+    LAST_WINDOW_ID.fetch_max(id, Ordering::Relaxed);
+
+    println!("[Rust-GLFW] Window created with ID: {}", id);
+    Ok(id)
+}
+
+// Détruit une fenêtre et purge toutes ses entrées par fenêtre : rien ne fuit, et un id
+// réutilisé n'hérite d'aucune donnée périmée. Tout nouveau registre indexé par id de
+// fenêtre doit être ajouté ici. Renvoie false si l'id est inconnu.
+fn destroy_window(state: &mut GlfwState, id: usize) -> bool {
+    // Le drop du PWindow appelle glfwDestroyWindow
+    if state.windows.remove(&id).is_none() {
+        return false;
+    }
+    // Valeurs Aegis
+    state.window_callbacks.remove(&id);
+    state.window_tags.remove(&id);
+    // Géométrie et attributs mis en cache
+    state.restored_geometry.remove(&id);
+    state.windowed_geometry.remove(&id);
+    state.window_sizes.remove(&id);
+    state.size_limits.remove(&id);
+    state.aspect_ratios.remove(&id);
+    state.window_titles.remove(&id);
+    state.resizable.remove(&id);
+    state.cursor_modes.remove(&id);
+    state.cursor_mode_stacks.remove(&id);
+    state.cursor_hide_regions.remove(&id);
+    state.dpi_autoscale.remove(&id);
+    // Accumulateurs d'entrées et suivis alimentés par poll
+    state.frame_input.remove(id);
+    state.input.remove(id);
+    state.close_times.remove(&id);
+    state.last_resize_times.remove(&id);
+    state.ignore_next_close.remove(&id);
+    state.close_graces.remove(&id);
+    state.close_requests.remove(&id);
+    state.attention_pending.remove(&id);
+    state.fps_counters.remove(&id);
+    state.fades.remove(&id);
+    if state.focused_window == id {
+        state.focused_window = 0;
+    }
+    // GLFW détache le contexte courant de la fenêtre détruite
+    if state.current_context == id {
+        state.current_context = 0;
+    }
+
+    println!("[Rust-GLFW] Window destroyed: {}", id);
+    true
+}
+
+fn glfw_create_window_ex(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 {
+        return Err("Args: width, height, title".into());
+    }
+
+    let width = window_dimension("width", args[0].as_int()?)?;
+    let height = window_dimension("height", args[1].as_int()?)?;
+    let title = args[2].as_str()?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let id = create_window(state, width, height, &title)?;
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+
+    // Géométrie réellement accordée (le gestionnaire de fenêtres peut ignorer la taille demandée)
+    let (w, h) = window.get_size();
+    let (fb_w, fb_h) = window.get_framebuffer_size();
+    let (scale_x, scale_y) = if feature_supported("content_scale").unwrap_or(false) {
+        window.get_content_scale()
+    } else {
+        (1.0, 1.0)
+    };
+    Ok(make_map(vec![
+        ("id", Value::Integer(id as i64)),
+        ("width", Value::Integer(w as i64)),
+        ("height", Value::Integer(h as i64)),
+        ("fb_width", Value::Integer(fb_w as i64)),
+        ("fb_height", Value::Integer(fb_h as i64)),
+        ("scale_x", Value::Float(scale_x as f64)),
+        ("scale_y", Value::Float(scale_y as f64)),
+    ]))
+}
+
+fn glfw_window_should_close(args: Vec<Value>) -> Result<Value, String> {
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if state.auto_poll {
+        // Événements conservés pour le prochain glfw_poll_events (les plus anciens sautent au-delà du plafond)
+        pump_events(state);
+        state.pending_events.extend(state.event_buffer.drain(..));
+        let overflow = state.pending_events.len().saturating_sub(MAX_PENDING_EVENTS);
+        state.pending_events.drain(..overflow);
+    }
+
+    if let Some((window, _)) = state.windows.get(&id) {
+        return Ok(Value::Boolean(window.should_close()));
+    }
+    Ok(Value::Boolean(true))
+}
+
+fn glfw_swap_buffers(args: Vec<Value>) -> Result<Value, String> {
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if let Some((window, _)) = state.windows.get_mut(&id) {
+        require_swappable(window, id)?;
+        window.swap_buffers();
+        record_frame(state, id);
+    }
+    Ok(Value::Null)
+}
+
+// Une fenêtre NO_API (Vulkan) n'a pas de contexte, et sans contexte courant le programme n'a rien
+// pu dessiner : GLFW échouerait en silence ou afficherait une image noire
+fn require_swappable(window: &PWindow, id: usize) -> Result<(), String> {
+    if window.get_client_api() == glfw::ffi::NO_API {
+        return Err(format!("Cannot swap buffers: window {} has no OpenGL context (NO_API)", id));
+    }
+    if unsafe { glfw::ffi::glfwGetCurrentContext() }.is_null() {
+        return Err(format!("Cannot swap buffers: no OpenGL context is current (make window {} current first)", id));
+    }
+    Ok(())
+}
+
+// Un swap = une image : seul endroit où les compteurs de FPS avancent
+fn record_frame(state: &mut GlfwState, id: usize) {
+    let now = state.context.get_time();
+    state.fps_counters.entry(id).or_default().record(now, state.fps_sample_window);
+}
+
+fn glfw_poll_events(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    Ok(Value::Boolean(poll_into_queue(state)))
+}
+
+fn glfw_wait_events_timeout(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: timeout".into());
+    }
+
+    let timeout = value_as_float(&args[0])?;
+    // Négatif, NaN ou infini : GLFW rejetterait la valeur (ou attendrait indéfiniment)
+    if !timeout.is_finite() || timeout < 0.0 {
+        return Err(format!("Timeout must be a finite number of seconds >= 0, got {}", timeout));
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // 0 = retour immédiat, comme glfw_poll_events ; de même si des événements reportés
+    // par le plafond par fenêtre attendent déjà (GLFW les a livrés, il ne réveillerait pas)
+    if timeout > 0.0 && !state.events_deferred {
+        state.context.wait_events_timeout(timeout);
+    }
+    Ok(Value::Boolean(poll_into_queue(state)))
+}
+
+// Corps de glfw_poll_events : pompe les événements vers event_queue, vrai s'il y en a eu
+fn poll_into_queue(state: &mut GlfwState) -> bool {
+    // Aucun tableau n'est renvoyé : les événements vont dans la file de glfw_next_event
+    let any = !state.pending_events.is_empty();
+    pump_events(state);
+    state.frame_input.end_poll();
+    state.event_queue.extend(state.pending_events.drain(..));
+    state.event_queue.extend(state.event_buffer.drain(..));
+
+    // File bornée : les plus anciens sautent si personne ne la consomme
+    let overflow = state.event_queue.len().saturating_sub(MAX_PENDING_EVENTS);
+    state.event_queue.drain(..overflow);
+
+    any || state.last_drained > 0
+}
+
+fn glfw_poll_events_array(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    pump_events(state);
+    state.frame_input.end_poll();
+
+    // Le tableau renvoyé appartient à Aegis : il est forcément neuf, mais alloué
+    // une seule fois à la bonne taille. Les événements de l'auto-poll passent en premier.
+    let mut records = Vec::with_capacity(state.pending_events.len() + state.event_buffer.len());
+    records.append(&mut state.pending_events);
+    records.append(&mut state.event_buffer);
+    Ok(make_array(records))
+}
+
+// Appelle poll_events puis vide les files de toutes les fenêtres, en un seul passage.
+// Les enregistrements d'événements sont ajoutés à state.event_buffer dans l'ordre
+// de traitement ; l'appelant le vide (drain/append) sans libérer sa capacité.
+//
+// Ordre des opérations :
+//   1. poll_events, puis transmission des erreurs GLFW survenues pendant le poll
+//   2. remise à zéro des accumulateurs de frame (défilement, transitions touches/boutons)
+//      si un poll visible les a rendus depuis le dernier pompage (voir FrameInput)
+//   3. pour chaque événement de chaque fenêtre, dans l'ordre de GLFW (au plus
+//      glfw_set_max_events_per_window par fenêtre) :
+//      a. Close avalé par glfw_ignore_next_close (rien d'autre n'est fait) ; un Close
+//         retenu par glfw_set_close_grace est en revanche enregistré
+//      b. conversion en enregistrement (+ seq) si sa catégorie passe le filtre
+//      c. mise à jour des accumulateurs par accumulate_input (indépendante du filtre), puis
+//         effets sur la fenêtre et mise en file des callbacks
+//   4. instant de passage à should_close de chaque fenêtre
+//   5. événements globaux : joystick, moniteur, puis utilisateur
+//      (indépendants des fenêtres : sans aucune fenêtre, seules ces étapes produisent quelque chose)
+//   6. destruction des fenêtres fermées si glfw_set_auto_destroy_closed (enregistrement "destroyed")
+//   7. avancement des fondus d'opacité (glfw_fade_window)
+fn pump_events(state: &mut GlfwState) {
+    // Trois lectures d'horloge par pompage, pour ne pas fausser la mesure : début, fin de
+    // poll_events (instant des événements globaux, de should_close et des fondus) et fin de conversion
+    let started = state.context.get_time();
+    state.context.poll_events();
+    flush_errors(state);
+    let now = state.context.get_time();
+
+    let records = &mut state.event_buffer;
+    let seq = &mut state.next_event_seq;
+    let mut drained = 0;
+    let cap = state.max_events_per_window.unwrap_or(usize::MAX);
+    state.events_deferred = false;
+    state.frame_input.begin_pump();
+    for (id, (window, events)) in state.windows.iter_mut() {
+        // La géométrie "normale" n'est mise à jour que hors iconification/maximisation
+        let normal = !window.is_iconified() && !window.is_maximized();
+        let mut taken = 0;
+        // flush_messages lit la file à la demande : ce qui dépasse le plafond y reste
+        for (time, event) in glfw::flush_messages(events).take(cap) {
+            drained += 1;
+            taken += 1;
+            // Fermeture avalée : ni enregistrement, ni should_close (GLFW l'a déjà posé, on l'annule)
+            if matches!(event, WindowEvent::Close) && state.ignore_next_close.remove(id) {
+                window.set_should_close(false);
+                continue;
+            }
+            // Le filtre n'évite que la conversion : les accumulateurs restent à jour
+            if state.event_filter & event_category(&event) != 0 {
+                if let Some(record) = window_event_record(*id, time, &event) {
+                    // Nom selon la disposition clavier active, résolu ici plutôt qu'un appel par événement côté Aegis
+                    if state.event_key_names {
+                        if let (WindowEvent::Key(key, scancode, ..), Value::Map(map)) = (&event, &record) {
+                            let name = glfw::get_key_name(Some(*key), Some(*scancode));
+                            map.borrow_mut().insert("name".to_string(), name.map(Value::Str).unwrap_or(Value::Null));
+                        }
+                    }
+                    push_record(records, seq, record);
+                }
+            }
+
+            accumulate_input(&mut state.frame_input, &mut state.input, *id, time, &event);
+
+            match event {
+                // Délai de grâce : le premier Close est retenu (should_close annulé) ; un Close
+                // arrivant une fois le délai écoulé confirme la demande
+                WindowEvent::Close => {
+                    if let Some(&grace) = state.close_graces.get(id) {
+                        if state.close_requests.get(id).is_some_and(|since| time - since >= grace) {
+                            state.close_requests.remove(id);
+                        } else {
+                            state.close_requests.entry(*id).or_insert(time);
+                            window.set_should_close(false);
+                        }
+                    }
+                }
+                WindowEvent::Scroll(dx, dy) => {
+                    if let Some(callback) = state.window_callbacks.get(id).and_then(|c| c.scroll.as_ref()) {
+                        queue_callback(&mut state.callback_queue, callback, vec![
+                            Value::Integer(*id as i64),
+                            Value::Float(dx),
+                            Value::Float(dy),
+                        ]);
+                    }
+                }
+                WindowEvent::Key(key, scancode, action, mods) => {
+                    if let Some(callback) = state.window_callbacks.get(id).and_then(|c| c.key.as_ref()) {
+                        queue_callback(&mut state.callback_queue, callback, vec![
+                            Value::Integer(*id as i64),
+                            Value::Integer(key as i64),
+                            Value::Integer(scancode as i64),
+                            Value::Integer(action_code(action)),
+                            Value::Integer(mods_code(mods)),
+                        ]);
+                    }
+                }
+                WindowEvent::MouseButton(button, action, mods) => {
+                    if let Some(callback) = state.window_callbacks.get(id).and_then(|c| c.mouse_button.as_ref()) {
+                        queue_callback(&mut state.callback_queue, callback, vec![
+                            Value::Integer(*id as i64),
+                            Value::Integer(button as i64),
+                            Value::Integer(action_code(action)),
+                            Value::Integer(mods_code(mods)),
+                        ]);
+                    }
+                }
+                WindowEvent::Char(codepoint) => {
+                    if let Some(callback) = state.window_callbacks.get(id).and_then(|c| c.char.as_ref()) {
+                        queue_callback(&mut state.callback_queue, callback, vec![
+                            Value::Integer(*id as i64),
+                            Value::Integer(codepoint as i64),
+                        ]);
+                    }
+                }
+                WindowEvent::CursorPos(x, y) => {
+                    // Zone de masquage : HIDDEN dedans, NORMAL dehors (un mode DISABLED n'est pas touché)
+                    if let Some(&(rx, ry, rw, rh)) = state.cursor_hide_regions.get(id) {
+                        let inside = x >= rx && x < rx + rw && y >= ry && y < ry + rh;
+                        let wanted = if inside { CURSOR_HIDDEN } else { CURSOR_NORMAL };
+                        let current = state.cursor_modes.entry(*id).or_insert(CURSOR_NORMAL);
+                        if *current != CURSOR_DISABLED && *current != wanted {
+                            window.set_cursor_mode(if inside { glfw::CursorMode::Hidden } else { glfw::CursorMode::Normal });
+                            *current = wanted;
+                        }
+                    }
+                    if let Some(callback) = state.window_callbacks.get(id).and_then(|c| c.cursor_pos.as_ref()) {
+                        queue_callback(&mut state.callback_queue, callback, vec![
+                            Value::Integer(*id as i64),
+                            Value::Float(x),
+                            Value::Float(y),
+                        ]);
+                    }
+                }
+                WindowEvent::ContentScale(sx, sy) => {
+                    // Sur macOS la taille est déjà en points logiques : rien à compenser
+                    if let Some(dpi) = state.dpi_autoscale.get_mut(id) {
+                        dpi.scale = (sx, sy);
+                        if !cfg!(target_os = "macos") {
+                            let w = (dpi.logical_size.0 * sx as f64).round() as i32;
+                            let h = (dpi.logical_size.1 * sy as f64).round() as i32;
+                            window.set_size(w.max(1), h.max(1));
+                        }
+                    }
+                }
+                WindowEvent::Size(w, h) => {
+                    // Redimensionnement (utilisateur ou autoscale) : nouvelle taille logique de référence
+                    if let Some(dpi) = state.dpi_autoscale.get_mut(id) {
+                        dpi.logical_size = (w as f64 / dpi.scale.0 as f64, h as f64 / dpi.scale.1 as f64);
+                    }
+                    state.window_sizes.insert(*id, (w, h));
+                    state.last_resize_times.insert(*id, time);
+                    if normal {
+                        if let Some(geometry) = state.restored_geometry.get_mut(id) {
+                            geometry.2 = w;
+                            geometry.3 = h;
+                        }
+                    }
+                }
+                WindowEvent::Focus(true) => {
+                    state.focused_window = *id;
+                    // Certaines plateformes laissent clignoter la barre des tâches après le clic :
+                    // un focus explicite arrête le clignotement
+                    if state.attention_pending.remove(id) && state.auto_clear_attention {
+                        window.focus();
+                    }
+                }
+                // Les fenêtres étant parcourues dans un ordre quelconque, le gain de focus d'une
+                // autre fenêtre a pu être vu avant cette perte : on n'efface que notre propre focus
+                WindowEvent::Focus(false) => {
+                    if state.focused_window == *id {
+                        state.focused_window = 0;
+                    }
+                }
+                WindowEvent::Pos(x, y) if normal => {
+                    if let Some(geometry) = state.restored_geometry.get_mut(id) {
+                        geometry.0 = x;
+                        geometry.1 = y;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Plafond atteint : il reste sans doute des événements (impossible de le savoir sans les lire)
+        if taken == cap {
+            state.events_deferred = true;
+        }
+
+        // Demande non confirmée dans un second délai de grâce : abandonnée
+        if let (Some(grace), Some(since)) = (state.close_graces.get(id), state.close_requests.get(id)) {
+            if now - since > 2.0 * grace {
+                state.close_requests.remove(id);
+            }
+        }
+
+        // On note la transition vers should_close (et on l'oublie si elle est annulée)
+        if window.should_close() {
+            state.close_times.entry(*id).or_insert(now);
+        } else {
+            state.close_times.remove(id);
+        }
+    }
+
+    // Événements globaux : non liés à une fenêtre, ils portent window = 0
+    for (jid, connected) in JOYSTICK_EVENTS.lock().unwrap().drain(..) {
+        drained += 1;
+        if state.event_filter & EVENT_JOYSTICK == 0 {
+            continue;
+        }
+        push_record(records, seq, event_record("joystick", 0, now, vec![
+            ("jid", Value::Integer(jid as i64)),
+            ("connected", Value::Boolean(connected)),
+        ]));
+    }
+
+    for (ptr, connected) in MONITOR_EVENTS.lock().unwrap().drain(..) {
+        drained += 1;
+        let ptr = ptr as *mut glfw::ffi::GLFWmonitor;
+        let id = if connected {
+            let id = state.next_monitor_id;
+            state.next_monitor_id += 1;
+            state.monitors.insert(id, ptr);
+            id
+        } else {
+            // L'id d'un moniteur débranché est invalidé définitivement
+            let id = state.monitors.iter().find(|(_, p)| **p == ptr).map(|(id, _)| *id);
+            match id {
+                Some(id) => { state.monitors.remove(&id); id }
+                None => continue,
+            }
+        };
+
+        if let Some(callback) = &state.monitor_callback {
+            queue_callback(&mut state.callback_queue, callback, vec![
+                Value::Integer(id as i64),
+                Value::Boolean(connected),
+            ]);
+        }
+        if state.event_filter & EVENT_MONITOR != 0 {
+            push_record(records, seq, event_record("monitor", 0, now, vec![
+                ("monitor", Value::Integer(id as i64)),
+                ("connected", Value::Boolean(connected)),
+            ]));
+        }
+    }
+
+    for UserEvent(payload) in USER_EVENTS.lock().unwrap().drain(..) {
+        drained += 1;
+        if state.event_filter & EVENT_USER == 0 {
+            continue;
+        }
+        push_record(records, seq, event_record("user", 0, now, vec![("payload", payload)]));
+    }
+
+    // Après les événements de la fenêtre : son "close" précède toujours son "destroyed"
+    if state.auto_destroy_closed {
+        let mut closed: Vec<usize> = state.windows.iter()
+            .filter(|(_, (window, _))| window.should_close())
+            .map(|(id, _)| *id)
+            .collect();
+        closed.sort_unstable();
+        for id in closed {
+            destroy_window(state, id);
+            if state.event_filter & EVENT_WINDOW != 0 {
+                push_record(&mut state.event_buffer, &mut state.next_event_seq, event_record("destroyed", id, now, vec![]));
+            }
+        }
+    }
+
+    LAST_EVENT_SEQ.store(state.next_event_seq - 1, Ordering::Relaxed);
+
+    // Un fondu terminé (ou dont la fenêtre a disparu) est retiré après avoir posé la cible
+    state.fades.retain(|id, fade| {
+        let Some((window, _)) = state.windows.get_mut(id) else { return false };
+        let t = ((now - fade.start) / fade.duration).clamp(0.0, 1.0);
+        window.set_opacity(fade.from + (fade.to - fade.from) * t as f32);
+        t < 1.0
+    });
+
+    state.last_drained = drained;
+    state.last_poll_duration = state.context.get_time() - started;
+}
+
+fn glfw_get_proc_address(_: Vec<Value>) -> Result<Value, String> {
+    let ptr = glfw::ffi::glfwGetProcAddress as usize;
+    Ok(Value::Integer(ptr as i64))
+}
+
+fn glfw_get_key(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, key_code".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let key_code = args[1].as_int()? as i32;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if let Some((window, _)) = state.windows.get(&id) {
+        // Convert raw int to GLFW Key enum (unsafe but necessary for raw binding)
+        // Or simpler: use glfw::Key::from_i32 if available, or just map manually.
+        // For simplicity in a dynamic binding, we trust the integer passed matches GLFW constants.
+        // Note: glfw-rs expects a Key enum. We need a way to cast int to Key.
+        // Since we can't easily cast int to Enum in safe Rust without a huge match,
+        // let's assume the user passes the correct ID.
+        
+        // Hack: Transmute int to Key (works because Key is repr(i32) usually)
+        // A cleaner way would be a huge match statement, but for a binding engine:
+        let key: glfw::Key = unsafe { std::mem::transmute(key_code) };
+        
+        let action = window.get_key(key);
+        // Returns true if Press or Repeat
+        return Ok(Value::Boolean(action == glfw::Action::Press || action == glfw::Action::Repeat));
+    }
+
+    Ok(Value::Boolean(false))
+}
+
+fn glfw_get_time(_: Vec<Value>) -> Result<Value, String> {
+    // Pas besoin du verrou STATE : glfwGetTime est thread-safe et c'est
+    // exactement ce qu'appelle context.get_time().
+    // Les boucles qui lisent l'horloge ne bloquent plus les opérations fenêtre.
+    // Hors session, glfwGetTime lève GLFW_NOT_INITIALIZED, qui s'accumulerait dans PENDING_ERRORS
+    if !GLFW_INITIALIZED.load(Ordering::Acquire) {
+        return Err("GLFW not initialized".into());
+    }
+    let time = unsafe { glfw::ffi::glfwGetTime() };
+    Ok(Value::Float(time))
+}
+
+fn glfw_get_restored_geometry(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (x, y, w, h) = *state.restored_geometry.get(&id)
+        .ok_or(format!("Unknown window id: {}", id))?;
+
+    Ok(make_map(vec![
+        ("x", Value::Integer(x as i64)),
+        ("y", Value::Integer(y as i64)),
+        ("w", Value::Integer(w as i64)),
+        ("h", Value::Integer(h as i64)),
+    ]))
+}
+
+fn glfw_get_joystick_guid(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: jid".into());
+    }
+
+    let jid = joystick_id(args[0].as_int()?)?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // GUID compatible SDL : stable d'une session à l'autre, contrairement au slot jid
+    match state.context.get_joystick(jid).get_guid() {
+        Some(guid) => Ok(Value::Str(guid)),
+        None => Ok(Value::Null),
+    }
+}
+
+fn glfw_set_window_tag(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, value".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    // Un tag Null efface simplement l'association
+    match &args[1] {
+        Value::Null => { state.window_tags.remove(&id); }
+        value => { state.window_tags.insert(id, value.clone()); }
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_get_window_tag(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    Ok(state.window_tags.get(&id).cloned().unwrap_or(Value::Null))
+}
+
+fn glfw_has_feature(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: name".into());
+    }
+
+    // Ne nécessite pas glfw_init : la version runtime est toujours disponible
+    let name = args[0].as_str()?;
+    let supported = feature_supported(&name).ok_or(format!("Unknown feature: {}", name))?;
+    Ok(Value::Boolean(supported))
+}
+
+fn glfw_monitor_changed(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Pas de callback GLFW fiable pour ça : on compare au dernier mode vu
+    let current = primary_video_mode(&mut state.context);
+    let same = match (&current, &state.last_video_mode) {
+        (Some(a), Some(b)) => a.width == b.width
+            && a.height == b.height
+            && a.refresh_rate == b.refresh_rate
+            && (a.red_bits, a.green_bits, a.blue_bits) == (b.red_bits, b.green_bits, b.blue_bits),
+        (None, None) => true,
+        _ => false,
+    };
+
+    state.last_video_mode = current;
+    Ok(Value::Boolean(!same))
+}
+
+fn glfw_get_input_state(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Tout est lu sous un seul verrou : instantané cohérent pour la frame
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+
+    let is_down = |action: glfw::Action| action == glfw::Action::Press || action == glfw::Action::Repeat;
+
+    let keys: Vec<Value> = KEY_CODES.iter()
+        .filter_map(|(_, code)| key_from_code(*code as i64).map(|key| (*code, key)))
+        .filter(|(_, key)| is_down(window.get_key(*key)))
+        .map(|(code, _)| Value::Integer(code as i64))
+        .collect();
+
+    let mouse_buttons: Vec<Value> = (0..8)
+        .filter_map(|code| mouse_button_from_code(code).map(|button| (code, button)))
+        .filter(|(_, button)| is_down(window.get_mouse_button(*button)))
+        .map(|(code, _)| Value::Integer(code))
+        .collect();
+
+    let (cursor_x, cursor_y) = window.get_cursor_pos();
+    let (scroll_x, scroll_y) = state.frame_input.scroll_deltas.get(&id).copied().unwrap_or((0.0, 0.0));
+    let mods = state.input.current_mods.get(&id).copied().unwrap_or(0);
+
+    Ok(make_map(vec![
+        ("keys", make_array(keys)),
+        ("mouse_buttons", make_array(mouse_buttons)),
+        ("cursor", make_map(vec![("x", Value::Float(cursor_x)), ("y", Value::Float(cursor_y))])),
+        ("scroll", make_map(vec![("x", Value::Float(scroll_x)), ("y", Value::Float(scroll_y))])),
+        ("mods", Value::Integer(mods as i64)),
+    ]))
+}
+
+fn glfw_terminate(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    // Rien à terminer (jamais initialisé, ou déjà terminé) : sans effet, comme glfwTerminate
+    let Some(state_wrapper) = guard.as_ref() else {
+        return Ok(Value::Null);
+    };
+
+    // GLFW doit être terminé depuis le thread qui l'a initialisé (sinon crash natif)
+    if state_wrapper.0.init_thread != std::thread::current().id() {
+        return Err("glfw_terminate must be called from the thread that called glfw_init".into());
+    }
+
+    // Le state sort de STATE et est libéré en entier ici (callbacks, tags, files d'événements) :
+    // aucune valeur Aegis ne survit à un cycle terminate/init
+    let ThreadSafeState(mut state) = guard.take().unwrap();
+    GLFW_INITIALIZED.store(false, Ordering::Release);
+    // Les champs sont libérés dans l'ordre de déclaration, context en premier : les fenêtres
+    // doivent l'être avant, le drop du dernier handle Glfw termine GLFW
+    state.windows.clear();
+    drop(state);
+
+    // Les files statiques survivent au state : des pointeurs de moniteur périmés y seraient dangereux,
+    // et des événements utilisateur postés avant terminate ressortiraient après le prochain init
+    JOYSTICK_EVENTS.lock().unwrap().clear();
+    MONITOR_EVENTS.lock().unwrap().clear();
+    PENDING_ERRORS.lock().unwrap().clear();
+    USER_EVENTS.lock().unwrap().clear();
+
+    println!("[Rust-GLFW] Terminated.");
+    Ok(Value::Null)
+}
+
+fn glfw_set_auto_poll(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: enabled".into());
+    }
+
+    let enabled = value_as_bool(&args[0])?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.auto_poll = enabled;
+    Ok(Value::Null)
+}
+
+fn glfw_set_clipboard_string(args: Vec<Value>) -> Result<Value, String> {
+    // Forme courte (text) ou avec fenêtre (win_id, text)
+    let (win_id, text) = match args.len() {
+        1 => (None, args[0].as_str()?),
+        2 => (Some(args[0].as_int()? as usize), args[1].as_str()?),
+        _ => return Err("Args: [win_id,] text".into()),
+    };
+
+    // glfw-rs passe par CString::new, qui panique sur un NUL interne
+    if let Some(pos) = text.find('\0') {
+        return Err(format!("Clipboard string contains a NUL byte at index {}", pos));
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    match win_id {
+        Some(id) => {
+            let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+            window.set_clipboard_string(&text);
+        }
+        None => {
+            // Contexte global : GLFW accepte une fenêtre NULL pour le presse-papiers
+            let c_text = std::ffi::CString::new(text).map_err(|e| format!("Invalid clipboard string: {}", e))?;
+            unsafe { glfw::ffi::glfwSetClipboardString(std::ptr::null_mut(), c_text.as_ptr()) };
+        }
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_get_clipboard_string(args: Vec<Value>) -> Result<Value, String> {
+    let win_id = match args.len() {
+        0 => None,
+        1 => Some(args[0].as_int()? as usize),
+        _ => return Err("Args: [win_id]".into()),
+    };
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Au-delà de la limite, le texte est tronqué (pas d'erreur) à une frontière de caractère
+    let text = match win_id {
+        Some(id) => {
+            let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+            read_clipboard(window.window_ptr(), state.clipboard_read_limit)
+        }
+        None => read_clipboard(std::ptr::null_mut(), state.clipboard_read_limit),
+    };
+
+    Ok(text.map(Value::Str).unwrap_or(Value::Null))
+}
+
+fn glfw_set_window_size_limits(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 5 {
+        return Err("Args: win_id, min_width, min_height, max_width, max_height".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let limits = SizeLimits {
+        min_width: size_limit(args[1].as_int()?),
+        min_height: size_limit(args[2].as_int()?),
+        max_width: size_limit(args[3].as_int()?),
+        max_height: size_limit(args[4].as_int()?),
+    };
+    limits.validate()?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    window.set_size_limits(limits.min_width, limits.min_height, limits.max_width, limits.max_height);
+    state.size_limits.insert(id, limits);
+    Ok(Value::Null)
+}
+
+fn glfw_set_window_size(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 {
+        return Err("Args: win_id, width, height".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut width = args[1].as_int()?;
+    let mut height = args[2].as_int()?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+
+    // On applique nous-mêmes les limites : le comportement natif varie selon les plateformes
+    let limits = state.size_limits.get(&id).copied().unwrap_or_default();
+    if let Some(min) = limits.min_width { width = width.max(min as i64); }
+    if let Some(min) = limits.min_height { height = height.max(min as i64); }
+    if let Some(max) = limits.max_width { width = width.min(max as i64); }
+    if let Some(max) = limits.max_height { height = height.min(max as i64); }
+
+    // GLFW n'impose le rapport qu'aux redimensionnements par l'utilisateur : ici la hauteur est
+    // déduite de la largeur. Si les limites de hauteur l'interdisent, c'est la largeur qui est
+    // déduite de la hauteur bornée (les limites de hauteur priment alors sur celles de largeur).
+    if let Some(&(numer, denom)) = state.aspect_ratios.get(&id) {
+        let (numer, denom) = (numer as f64, denom as f64);
+        let derived = (width as f64 * denom / numer).round() as i64;
+        let mut bounded = derived;
+        if let Some(min) = limits.min_height { bounded = bounded.max(min as i64); }
+        if let Some(max) = limits.max_height { bounded = bounded.min(max as i64); }
+        height = bounded;
+        if bounded != derived {
+            width = (bounded as f64 * numer / denom).round() as i64;
+        }
+    }
+    width = width.clamp(1, i32::MAX as i64);
+    height = height.clamp(1, i32::MAX as i64);
+
+    window.set_size(width as i32, height as i32);
+    Ok(make_array(vec![Value::Integer(width), Value::Integer(height)]))
+}
+
+fn glfw_window_close_time(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    Ok(state.close_times.get(&id).map(|t| Value::Float(*t)).unwrap_or(Value::Null))
+}
+
+fn glfw_set_window_opacity(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, alpha".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let alpha = value_as_float(&args[1])?.clamp(0.0, 1.0) as f32;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    require_opacity_support(window)?;
+
+    window.set_opacity(alpha);
+    Ok(Value::Null)
+}
+
+fn glfw_get_window_opacity(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    // Sans support (GLFW < 3.3, ou framebuffer opaque) la fenêtre est forcément opaque :
+    // 1.0 plutôt qu'une erreur. Seul le setter signale l'absence de support.
+    if !opacity_supported(window) {
+        return Ok(Value::Float(1.0));
+    }
+    Ok(Value::Float(window.get_opacity() as f64))
+}
+
+fn glfw_set_event_filter(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: mask".into());
+    }
+
+    let mask = args[0].as_int()?;
+    // Un bit inconnu (ou un masque négatif) trahit une faute de frappe : on le refuse plutôt que l'ignorer
+    let known = EVENT_FILTER_CODES.iter().fold(0, |all, (_, bits)| all | bits);
+    if mask & !known != 0 {
+        return Err(format!("Invalid event filter mask: {} (must combine EVENT_FILTER_* bits)", mask));
+    }
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.event_filter = mask;
+    Ok(Value::Null)
+}
+
+fn glfw_get_proc_addresses(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: names (array of strings)".into());
+    }
+
+    // get_proc_address_raw passe par CString::new, qui panique sur un NUL interne
+    let names = value_as_array(&args[0])?;
+    for name in &names {
+        let name = name.as_str()?;
+        if name.contains('\0') {
+            return Err(format!("Invalid proc name {:?}: contains a NUL byte", name));
+        }
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Les adresses GL dépendent du contexte courant : sans contexte, elles n'ont aucun sens
+    if unsafe { glfw::ffi::glfwGetCurrentContext() }.is_null() {
+        return Err("No current OpenGL context".into());
+    }
+
+    // Toute la table est résolue en un seul passage (0 = non résolue)
+    let mut addresses = Vec::with_capacity(names.len());
+    for name in &names {
+        let name = name.as_str()?;
+        let ptr = state.context.get_proc_address_raw(&name);
+        addresses.push(Value::Integer(ptr as usize as i64));
+    }
+    Ok(make_array(addresses))
+}
+
+fn glfw_init_hint(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: hint, value".into());
+    }
+
+    let hint = args[0].as_int()?;
+    let value = &args[1];
+
+    // Les init hints ne sont lus par GLFW qu'au moment de glfwInit
+    if STATE.lock().unwrap().is_some() {
+        return Err("glfw_init_hint must be called before glfw_init".into());
+    }
+
+    match hint {
+        INIT_HINT_JOYSTICK_HAT_BUTTONS => {
+            glfw::init_hint(glfw::InitHint::JoystickHatButtons(value_as_bool(value)?));
+        }
+        // Propres à macOS : acceptés (et ignorés par GLFW) ailleurs, pour un code portable
+        INIT_HINT_COCOA_CHDIR_RESOURCES => {
+            glfw::init_hint(glfw::InitHint::CocoaChdirResources(value_as_bool(value)?));
+        }
+        INIT_HINT_COCOA_MENUBAR => {
+            glfw::init_hint(glfw::InitHint::CocoaMenubar(value_as_bool(value)?));
+        }
+        INIT_HINT_PLATFORM | INIT_HINT_ANGLE_PLATFORM_TYPE => {
+            // Hints GLFW 3.4 : non exposés par glfw-rs, on passe par la FFI si le runtime les connaît
+            if !feature_supported("platform").unwrap_or(false) {
+                let version = glfw::get_version();
+                return Err(format!("Init hint {:#x} requires GLFW 3.4 (runtime is {}.{})", hint, version.major, version.minor));
+            }
+            let value = value.as_int()?;
+            unsafe { glfw::ffi::glfwInitHint(hint as std::os::raw::c_int, value as std::os::raw::c_int) };
+        }
+        _ => return Err(format!("Unknown init hint: {:#x}", hint)),
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_windows_should_close(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_ids (array)".into());
+    }
+
+    let ids = value_as_array(&args[0])?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Contrairement à glfw_window_should_close, un id inconnu est une erreur
+    let mut results = Vec::with_capacity(ids.len());
+    for id in &ids {
+        let id = id.as_int()? as usize;
+        let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+        results.push(Value::Boolean(window.should_close()));
+    }
+    Ok(make_array(results))
+}
+
+fn glfw_set_monitor_callback(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: callback (or null)".into());
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.monitor_callback = optional_callback(&args[0]);
+    Ok(Value::Null)
+}
+
+fn glfw_next_callback(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    // Un callback peut appeler glfw_terminate pendant le dispatch : la boucle s'arrête sans erreur
+    let Some(state_wrapper) = guard.as_mut() else {
+        return Ok(Value::Null);
+    };
+    let state = &mut state_wrapper.0;
+
+    Ok(state.callback_queue.pop_front().unwrap_or(Value::Null))
+}
+
+fn glfw_window_hint(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: hint, value".into());
+    }
+
+    let hint = args[0].as_int()?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Le hint s'applique à toutes les fenêtres créées ensuite
+    apply_window_hint(&mut state.context, hint, &args[1])?;
+
+    match window_hint_name(hint) {
+        // GLFW_ANY_POSITION (0x80000000) rend le placement au système
+        Some("POSITION_X") => state.position_hint.0 = position_hint_value(&args[1])?,
+        Some("POSITION_Y") => state.position_hint.1 = position_hint_value(&args[1])?,
+        Some("VISIBLE") => state.visible_hint = hint_bool(&args[1])?,
+        _ => {}
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_set_window_attrib(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 {
+        return Err("Args: win_id, attrib, value".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let attrib = args[1].as_int()?;
+    let enabled = hint_bool(&args[2])?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    let name = window_hint_name(attrib).ok_or(format!("Unknown window attrib: {:#x}", attrib))?;
+
+    match name {
+        "RESIZABLE" => {
+            window.set_resizable(enabled);
+            state.resizable.insert(id, enabled);
+        }
+        "DECORATED" => window.set_decorated(enabled),
+        "MOUSE_PASSTHROUGH" => {
+            // Modifiable à chaud (overlay cliquable / traversable)
+            require_feature("mouse_passthrough")?;
+            unsafe {
+                glfw::ffi::glfwSetWindowAttrib(window.window_ptr(), attrib as std::os::raw::c_int, enabled as std::os::raw::c_int)
+            };
+        }
+        _ => return Err(format!("Window attrib {} cannot be changed after creation", name)),
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_set_error_callback(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: callback (or null)".into());
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.error_callback = optional_callback(&args[0]);
+    Ok(Value::Null)
+}
+
+fn glfw_get_error(_: Vec<Value>) -> Result<Value, String> {
+    // Fonctionne sans init : les erreurs d'initialisation y sont aussi enregistrées.
+    // Comme glfwGetError, la lecture efface l'erreur.
+    match LAST_ERROR.lock().unwrap().take() {
+        Some((code, description)) => Ok(make_map(vec![
+            ("code", Value::Integer(code as i64)),
+            ("description", Value::Str(description)),
+        ])),
+        None => Ok(Value::Null),
+    }
+}
+
+fn glfw_get_window_size(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Valeur en cache : pas d'aller-retour vers l'OS à chaque frame
+    let (w, h) = *state.window_sizes.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    Ok(make_array(vec![Value::Integer(w as i64), Value::Integer(h as i64)]))
+}
+
+fn glfw_get_window_size_live(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    let (w, h) = window.get_size();
+    Ok(make_array(vec![Value::Integer(w as i64), Value::Integer(h as i64)]))
+}
+
+fn glfw_is_window_iconified(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    Ok(Value::Boolean(window.is_iconified()))
+}
+
+fn glfw_get_framebuffer_size(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+
+    // Fenêtre réduite : pas de surface à dessiner, certaines plateformes renvoient sinon une taille périmée
+    let (w, h) = if window.is_iconified() { (0, 0) } else { window.get_framebuffer_size() };
+    Ok(make_array(vec![Value::Integer(w as i64), Value::Integer(h as i64)]))
+}
+
+fn glfw_vulkan_supported(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    Ok(Value::Boolean(state.context.vulkan_supported()))
+}
+
+fn glfw_get_required_instance_extensions(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Null = Vulkan indisponible ; un tableau (même vide) = Vulkan utilisable
+    if !state.context.vulkan_supported() {
+        return Ok(Value::Null);
+    }
+    match state.context.get_required_instance_extensions() {
+        Some(extensions) => Ok(make_array(extensions.into_iter().map(Value::Str).collect())),
+        None => Ok(Value::Null),
+    }
+}
+
+fn glfw_set_scroll_callback(args: Vec<Value>) -> Result<Value, String> {
+    set_window_callback(args, |callbacks| &mut callbacks.scroll)
+}
+
+fn glfw_set_key_callback(args: Vec<Value>) -> Result<Value, String> {
+    set_window_callback(args, |callbacks| &mut callbacks.key)
+}
+
+fn glfw_set_mouse_button_callback(args: Vec<Value>) -> Result<Value, String> {
+    set_window_callback(args, |callbacks| &mut callbacks.mouse_button)
+}
+
+fn glfw_set_cursor_pos_callback(args: Vec<Value>) -> Result<Value, String> {
+    set_window_callback(args, |callbacks| &mut callbacks.cursor_pos)
+}
+
+fn glfw_set_char_callback(args: Vec<Value>) -> Result<Value, String> {
+    set_window_callback(args, |callbacks| &mut callbacks.char)
+}
+
+fn glfw_next_event(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Ne pompe rien : seul glfw_poll_events alimente la file
+    Ok(state.event_queue.pop_front().unwrap_or(Value::Null))
+}
+
+fn glfw_pause_time(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Déjà en pause : on garde l'instant initial
+    if state.time_paused_at.is_none() {
+        state.time_paused_at = Some(state.context.get_time());
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_resume_time(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if let Some(paused_at) = state.time_paused_at.take() {
+        state.paused_duration += state.context.get_time() - paused_at;
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_get_game_time(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Pendant une pause, l'horloge de jeu reste figée à l'instant de la pause
+    let now = state.time_paused_at.unwrap_or_else(|| state.context.get_time());
+    Ok(Value::Float(now - state.paused_duration))
+}
+
+fn glfw_set_window_monitor(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 7 {
+        return Err("Args: win_id, monitor_id, x, y, width, height, refresh_rate".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let monitor_id = args[1].as_int()? as usize;
+    let mut x = args[2].as_int()? as i32;
+    let mut y = args[3].as_int()? as i32;
+    let mut width = args[4].as_int()?;
+    let mut height = args[5].as_int()?;
+    let refresh_rate = args[6].as_int()?;
+    let refresh_rate = if refresh_rate <= 0 { None } else { Some(refresh_rate as u32) };
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+
+    let windowed = window.with_window_mode(|mode| matches!(mode, glfw::WindowMode::Windowed));
+
+    if monitor_id == 0 {
+        // Retour au fenêtré avec -1 partout : on restaure la géométrie mémorisée
+        if (x, y, width, height) == (-1, -1, -1, -1) {
+            let (px, py, pw, ph) = *state.windowed_geometry.get(&id)
+                .ok_or("No remembered windowed geometry for this window")?;
+            (x, y, width, height) = (px, py, pw as i64, ph as i64);
+        }
+        let width = window_dimension("width", width)?;
+        let height = window_dimension("height", height)?;
+        window.set_monitor(glfw::WindowMode::Windowed, x, y, width, height, refresh_rate);
+        return Ok(Value::Null);
+    }
+
+    let ptr = *state.monitors.get(&monitor_id).ok_or(format!("Unknown monitor id: {}", monitor_id))?;
+    let width = window_dimension("width", width)?;
+    let height = window_dimension("height", height)?;
+
+    // Mémorise le rectangle fenêtré avant de passer en plein écran
+    if windowed {
+        let (wx, wy) = window.get_pos();
+        let (ww, wh) = window.get_size();
+        state.windowed_geometry.insert(id, (wx, wy, ww, wh));
+    }
+
+    let applied = state.context.with_connected_monitors(|_, monitors| {
+        match monitors.iter().find(|m| m.as_ptr() == ptr) {
+            Some(monitor) => {
+                window.set_monitor(glfw::WindowMode::FullScreen(monitor), x, y, width, height, refresh_rate);
+                true
+            }
+            None => false,
+        }
+    });
+    if !applied {
+        return Err(format!("Unknown monitor id: {}", monitor_id));
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_clipboard_changed(_: Vec<Value>) -> Result<Value, String> {
+    use std::hash::{Hash, Hasher};
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Presse-papiers vide (None) : c'est un contenu comme un autre, pas une erreur
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    read_global_clipboard().hash(&mut hasher);
+    let hash = hasher.finish();
+
+    // Le premier appel ne fait qu'amorcer le cache
+    let changed = state.clipboard_hash.is_some_and(|previous| previous != hash);
+    state.clipboard_hash = Some(hash);
+    Ok(Value::Boolean(changed))
+}
+
+fn glfw_get_window_states(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+
+    // Un seul passage sur les attributs, sous un seul verrou
+    Ok(make_map(vec![
+        ("focused", Value::Boolean(window.is_focused())),
+        ("iconified", Value::Boolean(window.is_iconified())),
+        ("maximized", Value::Boolean(window.is_maximized())),
+        ("visible", Value::Boolean(window.is_visible())),
+        // HOVERED n'existe qu'à partir de GLFW 3.3 : false plutôt qu'une valeur indéfinie
+        ("hovered", Value::Boolean(feature_supported("window_hovered").unwrap_or(false) && window.is_hovered())),
+        ("resizable", Value::Boolean(state.resizable.get(&id).copied().unwrap_or_else(|| window.is_resizable()))),
+        ("decorated", Value::Boolean(window.is_decorated())),
+    ]))
+}
+
+fn glfw_last_poll_duration(_: Vec<Value>) -> Result<Value, String> {
+    let guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_ref().ok_or("GLFW not initialized")?;
+    let state = &state_wrapper.0;
+
+    Ok(Value::Float(state.last_poll_duration))
+}
+
+fn glfw_set_cursor_mode(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, mode".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let code = args[1].as_int()?;
+    let mode = cursor_mode_from_code(code)?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    window.set_cursor_mode(mode);
+    state.cursor_modes.insert(id, code);
+    // Le changement de mode fait sauter la position : on ne la compte pas comme un déplacement
+    state.input.cursor_last.remove(&id);
+    Ok(Value::Null)
+}
+
+fn glfw_set_cursor_pos(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 {
+        return Err("Args: win_id, x, y".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let x = value_as_float(&args[1])?;
+    let y = value_as_float(&args[2])?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+
+    // En mode DISABLED les coordonnées sont virtuelles et non bornées :
+    // repositionner le curseur n'a pas de sens, on le signale plutôt que de "réussir" en silence
+    if state.cursor_modes.get(&id) == Some(&CURSOR_DISABLED) {
+        return Err("Cannot set cursor position while the cursor mode is DISABLED".into());
+    }
+
+    window.set_cursor_pos(x, y);
+    Ok(Value::Null)
+}
+
+fn glfw_center_window(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, monitor_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut monitor_id = args[1].as_int()? as usize;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // monitor_id = 0 : le moniteur sur lequel se trouve la fenêtre
+    if monitor_id == 0 {
+        monitor_id = monitor_for_window(state, id)?.ok_or("Window is not on any monitor")?;
+    } else if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    let (wx, wy, ww, wh) = monitor_rects(state)
+        .into_iter()
+        .find(|rects| rects.id == monitor_id)
+        .map(|rects| rects.workarea)
+        .ok_or(format!("Unknown monitor id: {}", monitor_id))?;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    let (w, h) = window.get_size();
+    let x = wx + (ww - w) / 2;
+    let y = wy + (wh - h) / 2;
+    window.set_pos(x, y);
+
+    Ok(make_array(vec![Value::Integer(x as i64), Value::Integer(y as i64)]))
+}
+
+fn glfw_get_resize_settled(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, quiet_seconds".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let quiet_seconds = value_as_float(&args[1])?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    // Jamais redimensionnée : rien à attendre
+    let settled = match state.last_resize_times.get(&id) {
+        Some(last) => state.context.get_time() - last >= quiet_seconds,
+        None => true,
+    };
+    Ok(Value::Boolean(settled))
+}
+
+fn glfw_get_primary_monitor(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Système sans écran : 0, qui n'est jamais un id de moniteur valide
+    Ok(Value::Integer(primary_monitor_id(state).unwrap_or(0) as i64))
+}
+
+fn glfw_get_monitors(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let primary = primary_monitor_id(state);
+    let mut rects = monitor_rects(state);
+    // Ordre d'affichage : de gauche à droite, puis de haut en bas (l'id départage)
+    rects.sort_by_key(|r| (r.area.0, r.area.1, r.id));
+
+    let content_scale = feature_supported("content_scale").unwrap_or(false);
+    let mut details = Vec::with_capacity(rects.len());
+    for r in rects {
+        let detail = with_monitor(state, r.id, |m| (
+            m.get_name(),
+            if content_scale { m.get_content_scale() } else { (1.0, 1.0) },
+            m.get_video_mode().map(|mode| mode.refresh_rate).unwrap_or(0),
+            m.get_physical_size(),
+        ))?;
+        details.push((r, detail));
+    }
+
+    // Deux écrans identiques portent le même nom : display_name les numérote dans l'ordre d'affichage
+    let mut name_counts: HashMap<Option<String>, usize> = HashMap::new();
+    for (_, (name, ..)) in &details {
+        *name_counts.entry(name.clone()).or_default() += 1;
+    }
+    let mut name_ranks: HashMap<Option<String>, usize> = HashMap::new();
+
+    let mut monitors = Vec::with_capacity(details.len());
+    for (r, (name, (scale_x, scale_y), refresh_rate, (physical_w, physical_h))) in details {
+        let display_name = if name_counts[&name] > 1 {
+            let rank = name_ranks.entry(name.clone()).or_default();
+            *rank += 1;
+            format!("{} (#{})", name.as_deref().unwrap_or("Monitor"), rank)
+        } else {
+            name.clone().unwrap_or_else(|| "Monitor".to_string())
+        };
+        let (x, y, w, h) = r.area;
+        monitors.push(make_map(vec![
+            ("id", Value::Integer(r.id as i64)),
+            ("name", name.map(Value::Str).unwrap_or(Value::Null)),
+            ("display_name", Value::Str(display_name)),
+            ("primary", Value::Boolean(primary == Some(r.id))),
+            ("x", Value::Integer(x as i64)),
+            ("y", Value::Integer(y as i64)),
+            ("width", Value::Integer(w as i64)),
+            ("height", Value::Integer(h as i64)),
+            ("scale_x", Value::Float(scale_x as f64)),
+            ("scale_y", Value::Float(scale_y as f64)),
+            ("refresh_rate", Value::Integer(refresh_rate as i64)),
+            // 0 si le moniteur ne fournit pas ses dimensions (EDID absent, projecteur...)
+            ("physical_width_mm", Value::Integer(physical_w as i64)),
+            ("physical_height_mm", Value::Integer(physical_h as i64)),
+        ]));
+    }
+    Ok(make_array(monitors))
+}
+
+fn glfw_set_time(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: time".into());
+    }
+
+    let time = value_as_float(&args[0])?;
+    // Une valeur négative ou NaN rendrait get_time incohérent par la suite
+    if !time.is_finite() || time < 0.0 {
+        return Err(format!("Invalid time: {} (must be finite and >= 0)", time));
+    }
+    if time > MAX_GLFW_TIME {
+        return Err(format!("Invalid time: {} (must be <= {})", time, MAX_GLFW_TIME));
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.context.set_time(time);
+    // Les intervalles mesurés depuis l'ancienne horloge n'ont plus de sens
+    state.last_frame_time = None;
+    state.fixed_last_time = None;
+    state.fixed_accumulator = 0.0;
+    for counter in state.fps_counters.values_mut() {
+        counter.last_time = None;
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_get_cursor_delta(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    // La lecture remet le cumul à zéro
+    let (dx, dy) = state.input.cursor_deltas.remove(&id).unwrap_or((0.0, 0.0));
+    Ok(make_array(vec![Value::Float(dx), Value::Float(dy)]))
+}
+
+fn glfw_get_joystick_axes(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: jid".into());
+    }
+
+    let jid = joystick_id(args[0].as_int()?)?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Manette débranchée : GLFW ne renvoie aucun axe
+    let axes = state.context.get_joystick(jid).get_axes();
+    Ok(make_array(axes.into_iter().map(|a| Value::Float(a as f64)).collect()))
+}
+
+fn glfw_get_joystick_axes_deadzone(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: jid, deadzone".into());
+    }
+
+    let jid = joystick_id(args[0].as_int()?)?;
+    let deadzone = value_as_float(&args[1])?;
+    if !(0.0..1.0).contains(&deadzone) {
+        return Err(format!("Deadzone must be in [0, 1): {}", deadzone));
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let axes = state.context.get_joystick(jid).get_axes();
+    Ok(make_array(axes.into_iter().map(|a| Value::Float(apply_deadzone(a as f64, deadzone))).collect()))
+}
+
+fn glfw_set_context_fallback(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: enabled".into());
+    }
+
+    let enabled = value_as_bool(&args[0])?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.context_fallback = enabled;
+    Ok(Value::Null)
+}
+
+fn glfw_get_context_version(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    // Contexte réellement obtenu (peut différer des hints, notamment après un repli)
+    let version = window.get_context_version();
+    Ok(make_map(vec![
+        ("api", Value::Integer(window.get_client_api() as i64)),
+        ("major", Value::Integer(version.major as i64)),
+        ("minor", Value::Integer(version.minor as i64)),
+        ("revision", Value::Integer(version.patch as i64)),
+        ("profile", Value::Integer(window.get_opengl_profile() as i64)),
+    ]))
+}
+
+fn glfw_set_window_title(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, title".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let title = args[1].as_str()?;
+    check_title(&title)?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    window.set_title(&title);
+    state.window_titles.insert(id, title.to_string());
+    Ok(Value::Null)
+}
+
+fn glfw_set_window_title_fps(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, prefix".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let prefix = args[1].as_str()?;
+    check_title(&prefix)?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    let fps = state.fps_counters.get(&id).map_or(0.0, |counter| counter.average(state.fps_sample_window));
+    // Avant le deuxième swap : pas encore d'intervalle mesuré
+    let title = if fps > 0.0 {
+        format!("{} - {:.0} FPS", prefix, fps)
+    } else {
+        prefix.to_string()
+    };
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    window.set_title(&title);
+    state.window_titles.insert(id, title);
+    Ok(Value::Float(fps))
+}
+
+fn glfw_set_input_mode(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 {
+        return Err("Args: win_id, mode, value".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mode = args[1].as_int()?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    match mode {
+        INPUT_MODE_CURSOR => {
+            let code = args[2].as_int()?;
+            window.set_cursor_mode(cursor_mode_from_code(code)?);
+            state.cursor_modes.insert(id, code);
+            state.input.cursor_last.remove(&id);
+        }
+        INPUT_MODE_STICKY_KEYS => window.set_sticky_keys(value_as_bool(&args[2])?),
+        INPUT_MODE_STICKY_MOUSE_BUTTONS => window.set_sticky_mouse_buttons(value_as_bool(&args[2])?),
+        INPUT_MODE_LOCK_KEY_MODS => window.set_lock_key_mods(value_as_bool(&args[2])?),
+        INPUT_MODE_RAW_MOUSE_MOTION => {
+            require_feature("raw_mouse_motion")?;
+            window.set_raw_mouse_motion(value_as_bool(&args[2])?);
+        }
+        _ => return Err(format!("Unknown input mode: {:#x}", mode)),
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_get_input_mode(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, mode".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mode = args[1].as_int()?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    match mode {
+        // Code natif suivi par GlfwState (même valeur que celle passée au setter)
+        INPUT_MODE_CURSOR => Ok(Value::Integer(*state.cursor_modes.get(&id).unwrap_or(&CURSOR_NORMAL))),
+        INPUT_MODE_STICKY_KEYS => Ok(Value::Boolean(window.has_sticky_keys())),
+        INPUT_MODE_STICKY_MOUSE_BUTTONS => Ok(Value::Boolean(window.has_sticky_mouse_buttons())),
+        INPUT_MODE_LOCK_KEY_MODS => Ok(Value::Boolean(window.does_store_lock_key_mods())),
+        INPUT_MODE_RAW_MOUSE_MOTION => Ok(Value::Boolean(
+            feature_supported("raw_mouse_motion").unwrap_or(false) && window.uses_raw_mouse_motion(),
+        )),
+        _ => Err(format!("Unknown input mode: {:#x}", mode)),
+    }
+}
+
+fn glfw_request_window_attention(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    require_feature("window_attention")?;
+    window.request_attention();
+    state.attention_pending.insert(id);
+    Ok(Value::Null)
+}
+
+fn glfw_set_auto_clear_attention(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: enabled".into());
+    }
+
+    let enabled = value_as_bool(&args[0])?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.auto_clear_attention = enabled;
+    Ok(Value::Null)
+}
+
+fn glfw_get_platform(_: Vec<Value>) -> Result<Value, String> {
+    let guard = STATE.lock().unwrap();
+    // La plateforme n'est choisie qu'au moment de glfwInit
+    guard.as_ref().ok_or("GLFW not initialized")?;
+
+    require_feature("platform")?;
+    let code = unsafe { glfw::ffi::glfwGetPlatform() } as i64;
+    let name = PLATFORM_CODES.iter().find(|(_, c)| *c == code).map(|(name, _)| *name);
+    Ok(Value::Str(name.unwrap_or("unknown").to_string()))
+}
+
+fn glfw_platform_supported(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: platform".into());
+    }
+
+    // Nom ("x11", ...) ou code Glfw.Platform ; utilisable avant glfw_init
+    let code = match &args[0] {
+        Value::Str(name) => PLATFORM_CODES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, code)| *code)
+            .ok_or(format!("Unknown platform: {}", name))?,
+        other => other.as_int()?,
+    };
+    if !PLATFORM_CODES.iter().any(|(_, c)| *c == code) {
+        return Err(format!("Unknown platform: {:#x}", code));
+    }
+
+    require_feature("platform")?;
+    let supported = unsafe { glfw::ffi::glfwPlatformSupported(code as std::os::raw::c_int) } == glfw::ffi::TRUE;
+    Ok(Value::Boolean(supported))
+}
+
+fn glfw_enable_dpi_autoscale(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, enabled".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let enabled = value_as_bool(&args[1])?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    if !enabled {
+        state.dpi_autoscale.remove(&id);
+        return Ok(Value::Null);
+    }
+
+    require_feature("content_scale")?;
+    // La taille actuelle, rapportée à l'échelle actuelle, devient la référence
+    let scale = window.get_content_scale();
+    let (w, h) = window.get_size();
+    state.dpi_autoscale.insert(id, DpiAutoscale {
+        scale,
+        logical_size: (w as f64 / scale.0 as f64, h as f64 / scale.1 as f64),
+    });
+    Ok(Value::Null)
+}
+
+fn glfw_key_just_pressed(args: Vec<Value>) -> Result<Value, String> {
+    key_edge(args, true)
+}
+
+fn glfw_key_just_released(args: Vec<Value>) -> Result<Value, String> {
+    key_edge(args, false)
+}
+
+fn glfw_mouse_button_just_pressed(args: Vec<Value>) -> Result<Value, String> {
+    mouse_button_edge(args, true)
+}
+
+fn glfw_mouse_button_just_released(args: Vec<Value>) -> Result<Value, String> {
+    mouse_button_edge(args, false)
+}
+
+fn glfw_set_window_aspect_ratio(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 {
+        return Err("Args: win_id, numer, denom".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let numer = args[1].as_int()?;
+    let denom = args[2].as_int()?;
+    // (-1, -1) = GLFW_DONT_CARE : supprime la contrainte ; sinon les deux doivent être positifs
+    let ratio = match (numer, denom) {
+        (-1, -1) => None,
+        (n, d) if n > 0 && d > 0 && n <= i32::MAX as i64 && d <= i32::MAX as i64 => Some((n as u32, d as u32)),
+        _ => return Err(format!("Invalid aspect ratio {}:{} (use -1, -1 to unlock)", numer, denom)),
+    };
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    match ratio {
+        Some((n, d)) => {
+            window.set_aspect_ratio(n, d);
+            state.aspect_ratios.insert(id, (n, d));
+        }
+        None => {
+            unsafe { glfw::ffi::glfwSetWindowAspectRatio(window.window_ptr(), glfw::ffi::DONT_CARE, glfw::ffi::DONT_CARE) };
+            state.aspect_ratios.remove(&id);
+        }
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_get_window_aspect_ratio(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    // GLFW n'a pas de getter : valeur suivie par glfw_set_window_aspect_ratio
+    match state.aspect_ratios.get(&id) {
+        Some((n, d)) => Ok(make_array(vec![Value::Integer(*n as i64), Value::Integer(*d as i64)])),
+        None => Ok(Value::Null),
+    }
+}
+
+fn glfw_get_window_title(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    Ok(Value::Str(state.window_titles.get(&id).cloned().unwrap_or_default()))
+}
+
+fn glfw_get_joystick_battery(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: jid".into());
+    }
+
+    joystick_id(args[0].as_int()?)?;
+    let guard = STATE.lock().unwrap();
+    guard.as_ref().ok_or("GLFW not initialized")?;
+
+    // Ni GLFW (jusqu'à 3.4) ni glfw-rs n'exposent le niveau de batterie : -1 = inconnu,
+    // quelle que soit la manette. Le contrat (pourcentage ou -1) permet de l'ajouter plus tard.
+    Ok(Value::Integer(-1))
+}
+
+fn glfw_swap_buffers_timed(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    require_swappable(window, id)?;
+
+    // Durée bloquée dans swap_buffers : proche d'une période d'écran = attente de vsync
+    let started = state.context.get_time();
+    window.swap_buffers();
+    let blocked = state.context.get_time() - started;
+    record_frame(state, id);
+    Ok(Value::Float(blocked))
+}
+
+fn glfw_set_window_should_close(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, value".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let value = value_as_bool(&args[1])?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    window.set_should_close(value);
+    Ok(Value::Null)
+}
+
+fn glfw_ignore_next_close(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    state.ignore_next_close.insert(id);
+    Ok(Value::Null)
+}
+
+fn glfw_post_user_event(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: payload".into());
+    }
+
+    // Seuls les scalaires traversent les threads (voir UserEvent)
+    let payload = match &args[0] {
+        Value::Null | Value::Boolean(_) | Value::Integer(_) | Value::Float(_) | Value::Str(_) => args[0].clone(),
+        _ => return Err("User event payload must be null, bool, int, float or string".into()),
+    };
+
+    // Pas de verrou sur STATE : appelable depuis un thread de travail
+    USER_EVENTS.lock().unwrap().push_back(UserEvent(payload));
+    // Réveille un thread principal bloqué dans l'attente d'événements (thread-safe côté GLFW)
+    unsafe { glfw::ffi::glfwPostEmptyEvent() };
+    Ok(Value::Null)
+}
+
+fn glfw_set_clipboard_read_limit(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: bytes".into());
+    }
+
+    // -1 = illimité (par défaut)
+    let bytes = args[0].as_int()?;
+    let limit = match bytes {
+        -1 => None,
+        b if b >= 0 => Some(b as usize),
+        _ => return Err(format!("Invalid clipboard read limit: {} (use -1 for unlimited)", bytes)),
+    };
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.clipboard_read_limit = limit;
+    Ok(Value::Null)
+}
+
+fn glfw_get_window_attrib(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, attrib".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let attrib = args[1].as_int()?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    let name = window_hint_name(attrib).ok_or(format!("Unknown window attrib: {:#x}", attrib))?;
+    let code = attrib as std::os::raw::c_int;
+
+    let version = glfw::get_version();
+    check_attrib_version(name, (version.major, version.minor))?;
+
+    // Valeurs réellement obtenues, qui peuvent différer des hints demandés
+    match name {
+        "RESIZABLE" | "VISIBLE" | "DECORATED" | "MAXIMIZED" | "TRANSPARENT_FRAMEBUFFER" | "HOVERED"
+        | "FOCUSED" | "AUTO_ICONIFY" | "FLOATING" | "FOCUS_ON_SHOW" | "DOUBLEBUFFER" | "OPENGL_FORWARD_COMPAT" | "CONTEXT_NO_ERROR" | "MOUSE_PASSTHROUGH" => {
+            Ok(Value::Boolean(window_attrib(window, code)))
+        }
+        "CLIENT_API" | "CONTEXT_VERSION_MAJOR" | "CONTEXT_VERSION_MINOR" | "OPENGL_PROFILE"
+        | "CONTEXT_RELEASE_BEHAVIOR" | "CONTEXT_CREATION_API" => {
+            let value = unsafe { glfw::ffi::glfwGetWindowAttrib(window.window_ptr(), code) };
+            Ok(Value::Integer(value as i64))
+        }
+        _ => Err(format!("Window attrib {} cannot be read", name)),
+    }
+}
+
+// Lit (major, minor) pour les raccourcis de sélection de contexte
+fn context_version_args(args: &[Value]) -> Result<(u32, u32), String> {
+    if args.len() != 2 {
+        return Err("Args: major, minor".into());
+    }
+    let major = args[0].as_int()?;
+    let minor = args[1].as_int()?;
+    if !(1..=9).contains(&major) || !(0..=9).contains(&minor) {
+        return Err(format!("Invalid context version: {}.{}", major, minor));
+    }
+    Ok((major as u32, minor as u32))
+}
+
+fn glfw_use_opengl_es(args: Vec<Value>) -> Result<Value, String> {
+    let (major, minor) = context_version_args(&args)?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    use glfw::WindowHint;
+    state.context.window_hint(WindowHint::ClientApi(glfw::ClientApiHint::OpenGlEs));
+    state.context.window_hint(WindowHint::ContextVersion(major, minor));
+    // Sous Linux, EGL est le chemin fiable pour ES (y compris Wayland) ; ailleurs l'API native
+    // (WGL/ANGLE) suffit. Profil et forward-compat n'ont pas de sens en ES : valeurs neutres.
+    state.context.window_hint(WindowHint::ContextCreationApi(if cfg!(target_os = "linux") {
+        glfw::ContextCreationApi::Egl
+    } else {
+        glfw::ContextCreationApi::Native
+    }));
+    state.context.window_hint(WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Any));
+    state.context.window_hint(WindowHint::OpenGlForwardCompat(false));
+    Ok(Value::Null)
+}
+
+fn glfw_use_opengl_core(args: Vec<Value>) -> Result<Value, String> {
+    let (major, minor) = context_version_args(&args)?;
+    // Le profil core n'existe qu'à partir d'OpenGL 3.2
+    if (major, minor) < (3, 2) {
+        return Err(format!("OpenGL core profile requires version 3.2 or later, got {}.{}", major, minor));
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    use glfw::WindowHint;
+    state.context.window_hint(WindowHint::ClientApi(glfw::ClientApiHint::OpenGl));
+    state.context.window_hint(WindowHint::ContextCreationApi(glfw::ContextCreationApi::Native));
+    state.context.window_hint(WindowHint::ContextVersion(major, minor));
+    state.context.window_hint(WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+    // Obligatoire sur macOS, sans effet gênant ailleurs pour un contexte core
+    state.context.window_hint(WindowHint::OpenGlForwardCompat(true));
+    Ok(Value::Null)
+}
+
+fn glfw_frame_delta(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Premier appel : 0.0 plutôt que le temps écoulé depuis glfw_init
+    let now = state.context.get_time();
+    let delta = state.last_frame_time.replace(now).map_or(0.0, |last| now - last);
+    Ok(Value::Float(delta))
+}
+
+fn glfw_set_window_icon(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, image".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let image = pixel_image(&args[1])?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    window.set_icon_from_pixels(vec![image]);
+    Ok(Value::Null)
+}
+
+fn glfw_set_window_icon_multi(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, images (array)".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    // Toutes les images sont validées avant de toucher à la fenêtre
+    let images = value_as_array(&args[1])?
+        .iter()
+        .enumerate()
+        .map(|(i, image)| pixel_image(image).map_err(|e| format!("Icon image {}: {}", i, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    // GLFW choisit la taille la plus proche de chaque usage ; un tableau vide rétablit l'icône par défaut
+    window.set_icon_from_pixels(images);
+    Ok(Value::Null)
+}
+
+fn glfw_set_id_strategy(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: monotonic".into());
+    }
+
+    // Pris en compte au prochain glfw_init ; sans verrou, utilisable avant l'init
+    MONOTONIC_WINDOW_IDS.store(value_as_bool(&args[0])?, Ordering::Relaxed);
+    Ok(Value::Null)
+}
+
+fn glfw_get_cursor_pos(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    // Brut : peut sortir de la zone client pendant un glisser (négatif ou au-delà de la taille)
+    let (x, y) = window.get_cursor_pos();
+    Ok(make_array(vec![Value::Float(x), Value::Float(y)]))
+}
+
+fn glfw_get_cursor_pos_clamped(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get(&id).ok_or(format!("Unknown window id: {}", id))?;
+    let (x, y) = window.get_cursor_pos();
+    // Taille mémorisée par poll, pour éviter un aller-retour vers le système à chaque appel
+    let (w, h) = state.window_sizes.get(&id).copied().unwrap_or((0, 0));
+    Ok(make_array(vec![
+        Value::Float(x.clamp(0.0, w.max(0) as f64)),
+        Value::Float(y.clamp(0.0, h.max(0) as f64)),
+    ]))
+}
+
+fn glfw_poll_events_grouped(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    pump_events(state);
+    state.frame_input.end_poll();
+
+    // Clés = id de fenêtre en texte (les maps Aegis sont indexées par chaîne) + "global".
+    // Chaque fenêtre ouverte a sa clé, même sans événement : pas de test d'existence côté Aegis.
+    let mut groups: HashMap<String, Vec<Value>> = state.windows.keys().map(|id| (id.to_string(), Vec::new())).collect();
+    groups.insert("global".to_string(), Vec::new());
+
+    let records = state.pending_events.drain(..).chain(state.event_buffer.drain(..));
+    for record in records {
+        let window = map_field(&record, "window")?.as_int()?;
+        let key = if window == 0 { "global".to_string() } else { window.to_string() };
+        // Une fenêtre détruite entre l'auto-poll et ce poll garde ses derniers événements
+        groups.entry(key).or_default().push(record);
+    }
+
+    let groups = groups.into_iter().map(|(key, events)| (key, make_array(events))).collect();
+    Ok(Value::Map(Rc::new(RefCell::new(groups))))
+}
+
+fn glfw_match_video_mode(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 4 {
+        return Err("Args: monitor_id, width, height, refresh_rate".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let width = window_dimension("width", args[1].as_int()?)? as i64;
+    let height = window_dimension("height", args[2].as_int()?)? as i64;
+    let refresh_rate = args[3].as_int()?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let modes = with_monitor(state, id, |m| m.get_video_modes())?;
+    // Résolution d'abord, puis fréquence, puis la plus grande profondeur de couleur
+    let best = modes.iter().min_by_key(|mode| (
+        (mode.width as i64 - width).abs() + (mode.height as i64 - height).abs(),
+        (mode.refresh_rate as i64 - refresh_rate).abs(),
+        -((mode.red_bits + mode.green_bits + mode.blue_bits) as i64),
+    ));
+    Ok(best.map(video_mode_record).unwrap_or(Value::Null))
+}
+
+fn glfw_set_window_resizable(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, resizable".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let resizable = value_as_bool(&args[1])?;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    window.set_resizable(resizable);
+    state.resizable.insert(id, resizable);
+    Ok(Value::Null)
+}
+
+fn glfw_get_error_log(_: Vec<Value>) -> Result<Value, String> {
+    // Sans init ni effacement (contrairement à glfw_get_error), du plus ancien au plus récent
+    let log = ERROR_LOG.lock().unwrap();
+    Ok(make_array(log.iter().map(|(code, description)| make_map(vec![
+        ("code", Value::Integer(*code as i64)),
+        ("description", Value::Str(description.clone())),
+    ])).collect()))
+}
+
+fn glfw_clear_error_log(_: Vec<Value>) -> Result<Value, String> {
+    ERROR_LOG.lock().unwrap().clear();
+    Ok(Value::Null)
+}
+
+fn glfw_get_gamepad_axis(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: jid, axis_name".into());
+    }
+
+    let jid = joystick_id(args[0].as_int()?)?;
+    let name = args[1].as_str()?;
+    let axis = GAMEPAD_AXES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, axis)| *axis)
+        .ok_or(format!("Unknown gamepad axis: {}", name))?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Manette absente, sans mapping connu ou GLFW < 3.3 : 0.0 (position neutre)
+    if !feature_supported("gamepad").unwrap_or(false) {
+        return Ok(Value::Float(0.0));
+    }
+    let value = state.context
+        .get_joystick(jid)
+        .get_gamepad_state()
+        .map_or(0.0, |gamepad| gamepad.get_axis(axis));
+    Ok(Value::Float(value as f64))
+}
+
+fn glfw_set_borderless_fullscreen(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, monitor_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let monitor_id = args[1].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // 0 = moniteur courant de la fenêtre (celui qu'elle recouvre le plus), sinon le principal
+    let monitor_id = if monitor_id == 0 {
+        match monitor_for_window(state, id)? {
+            Some(monitor_id) => monitor_id,
+            None => primary_monitor_id(state).ok_or("No monitor available")?,
+        }
+    } else {
+        monitor_id
+    };
+    let ptr = *state.monitors.get(&monitor_id).ok_or(format!("Unknown monitor id: {}", monitor_id))?;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    if window.with_window_mode(|mode| matches!(mode, glfw::WindowMode::Windowed)) {
+        let (wx, wy) = window.get_pos();
+        let (ww, wh) = window.get_size();
+        state.windowed_geometry.insert(id, (wx, wy, ww, wh));
+    }
+
+    // Mode vidéo courant du moniteur, fréquence comprise : GLFW ne change pas de mode
+    // et se contente d'une fenêtre sans bordure qui couvre l'écran (pas de clignotement)
+    let applied = state.context.with_connected_monitors(|_, monitors| {
+        let monitor = monitors.iter().find(|m| m.as_ptr() == ptr)?;
+        let mode = monitor.get_video_mode()?;
+        window.set_monitor(glfw::WindowMode::FullScreen(monitor), 0, 0, mode.width, mode.height, Some(mode.refresh_rate));
+        Some(())
+    });
+    applied.ok_or(format!("Unknown monitor id: {}", monitor_id))?;
+    Ok(Value::Null)
+}
+
+fn glfw_set_cursor_hide_region(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 5 {
+        return Err("Args: win_id, x, y, w, h".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let x = value_as_float(&args[1])?;
+    let y = value_as_float(&args[2])?;
+    let w = value_as_float(&args[3])?;
+    let h = value_as_float(&args[4])?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+
+    // Rectangle vide : comportement désactivé, et le curseur caché par la zone réapparaît
+    if w <= 0.0 || h <= 0.0 {
+        if state.cursor_hide_regions.remove(&id).is_some() && state.cursor_modes.get(&id) == Some(&CURSOR_HIDDEN) {
+            window.set_cursor_mode(glfw::CursorMode::Normal);
+            state.cursor_modes.insert(id, CURSOR_NORMAL);
+        }
+        return Ok(Value::Null);
+    }
+
+    // Appliqué dès le prochain déplacement du curseur (pendant poll)
+    state.cursor_hide_regions.insert(id, (x, y, w, h));
+    Ok(Value::Null)
+}
+
+fn glfw_get_primary_content_scale(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Sans écran (ou avant GLFW 3.3), l'échelle neutre permet de dimensionner quand même
+    let scale = match primary_monitor_id(state) {
+        Some(id) if feature_supported("content_scale").unwrap_or(false) => with_monitor(state, id, |m| m.get_content_scale())?,
+        _ => (1.0, 1.0),
+    };
+    Ok(make_array(vec![Value::Float(scale.0 as f64), Value::Float(scale.1 as f64)]))
+}
+
+fn glfw_fade_window(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 {
+        return Err("Args: win_id, target, seconds".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let target = value_as_float(&args[1])?.clamp(0.0, 1.0) as f32;
+    let seconds = value_as_float(&args[2])?;
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(format!("Invalid fade duration: {} (must be finite and >= 0)", seconds));
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    require_opacity_support(window)?;
+
+    // Durée nulle : appliqué tout de suite. Un nouveau fondu remplace celui en cours,
+    // en repartant de l'opacité atteinte.
+    if seconds == 0.0 {
+        state.fades.remove(&id);
+        window.set_opacity(target);
+        return Ok(Value::Null);
+    }
+    let from = window.get_opacity();
+    let start = state.context.get_time();
+    state.fades.insert(id, Fade { from, to: target, start, duration: seconds });
+    Ok(Value::Null)
+}
+
+fn glfw_get_keys_pressed(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+
+    let guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_ref().ok_or("GLFW not initialized")?;
+    let state = &state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    // Triées pour un affichage stable d'une frame à l'autre
+    let mut keys: Vec<i32> = state.input.held_keys.get(&id).map(|held| held.iter().copied().collect()).unwrap_or_default();
+    keys.sort_unstable();
+    Ok(make_array(keys.into_iter().map(|k| Value::Integer(k as i64)).collect()))
+}
+
+fn glfw_destroy_window(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Une fenêtre déjà détruite par l'auto-destruction est inconnue, comme les autres
+    if !destroy_window(state, id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_set_auto_destroy_closed(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: enabled".into());
+    }
+
+    let enabled = value_as_bool(&args[0])?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.auto_destroy_closed = enabled;
+    Ok(Value::Null)
+}
+
+fn glfw_fixed_timestep(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: step_seconds".into());
+    }
+
+    let step = value_as_float(&args[0])?;
+    if !step.is_finite() || step <= 0.0 {
+        return Err(format!("Invalid step: {} (must be finite and > 0)", step));
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Horloge propre : indépendante des appels à glfw_frame_delta
+    let now = state.context.get_time();
+    if let Some(last) = state.fixed_last_time.replace(now) {
+        state.fixed_accumulator += now - last;
+    }
+
+    let mut steps = (state.fixed_accumulator / step).floor() as i64;
+    if steps > MAX_FIXED_STEPS {
+        // Spirale de la mort évitée : on ne garde que la fraction de pas en cours
+        steps = MAX_FIXED_STEPS;
+        state.fixed_accumulator %= step;
+    } else {
+        state.fixed_accumulator -= steps as f64 * step;
+    }
+    Ok(Value::Integer(steps))
+}
+
+fn glfw_set_clipboard_bytes(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: bytes".into());
+    }
+
+    let items = value_as_array(&args[0])?;
+    if items.len() > MAX_CLIPBOARD_BYTES {
+        return Err(format!("Clipboard payload too large: {} bytes (max {})", items.len(), MAX_CLIPBOARD_BYTES));
+    }
+    let mut bytes = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let b = item.as_int()?;
+        let b = u8::try_from(b).map_err(|_| format!("Invalid byte at index {}: {} (must be 0-255)", index, b))?;
+        bytes.push(b);
+    }
+
+    let guard = STATE.lock().unwrap();
+    guard.as_ref().ok_or("GLFW not initialized")?;
+
+    // Le base64 ne contient jamais de NUL : les octets nuls du blob passent sans risque
+    let c_text = std::ffi::CString::new(base64_encode(&bytes)).map_err(|e| format!("Invalid clipboard string: {}", e))?;
+    unsafe { glfw::ffi::glfwSetClipboardString(std::ptr::null_mut(), c_text.as_ptr()) };
+    Ok(Value::Null)
+}
+
+fn glfw_get_clipboard_bytes(_: Vec<Value>) -> Result<Value, String> {
+    let guard = STATE.lock().unwrap();
+    guard.as_ref().ok_or("GLFW not initialized")?;
+
+    // Lecture bornée (un texte tronqué ne se décoderait pas) : ce qui dépasse n'est pas un blob à nous
+    let limit = MAX_CLIPBOARD_BYTES.div_ceil(3) * 4;
+    let bytes = read_clipboard(std::ptr::null_mut(), Some(limit + 1))
+        .filter(|text| text.len() <= limit)
+        .and_then(|text| base64_decode(&text));
+    Ok(bytes
+        .map(|bytes| make_array(bytes.into_iter().map(|b| Value::Integer(b as i64)).collect()))
+        .unwrap_or(Value::Null))
+}
+
+fn glfw_get_last_window(_: Vec<Value>) -> Result<Value, String> {
+    let guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_ref().ok_or("GLFW not initialized")?;
+    let state = &state_wrapper.0;
+
+    // Les ids sont croissants : le plus grand encore ouvert est le plus récent (0 = aucune fenêtre)
+    Ok(Value::Integer(state.windows.keys().max().copied().unwrap_or(0) as i64))
+}
+
+fn glfw_get_focused_window(_: Vec<Value>) -> Result<Value, String> {
+    let guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_ref().ok_or("GLFW not initialized")?;
+    let state = &state_wrapper.0;
+
+    Ok(Value::Integer(state.focused_window as i64))
+}
+
+fn glfw_get_gamma_ramp(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: monitor_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Monitor::get_gamma_ramp déréférence le pointeur sans vérifier : NULL (Wayland,
+    // pilote sans gamma) ferait planter, d'où l'appel direct
+    let channels = with_monitor(state, id, |m| unsafe {
+        let ramp = glfw::ffi::glfwGetGammaRamp(m.as_ptr());
+        if ramp.is_null() || (*ramp).size == 0 {
+            return None;
+        }
+        let size = (*ramp).size as usize;
+        let channel = |ptr: *mut std::os::raw::c_ushort| {
+            let values = std::slice::from_raw_parts(ptr, size);
+            make_array(values.iter().map(|v| Value::Integer(*v as i64)).collect())
+        };
+        Some((channel((*ramp).red), channel((*ramp).green), channel((*ramp).blue)))
+    })?;
+
+    Ok(channels
+        .map(|(red, green, blue)| make_map(vec![("red", red), ("green", green), ("blue", blue)]))
+        .unwrap_or(Value::Null))
+}
+
+fn glfw_get_cursor_velocity(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+
+    let guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_ref().ok_or("GLFW not initialized")?;
+    let state = &state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    // Horodatages des événements eux-mêmes : indépendant du rythme des frames
+    let now = state.context.get_time();
+    let (vx, vy) = match state.input.cursor_samples.get(&id) {
+        Some(&[(t0, x0, y0), (t1, x1, y1)]) if t1 > t0 && now - t1 <= CURSOR_VELOCITY_TIMEOUT => {
+            ((x1 - x0) / (t1 - t0), (y1 - y0) / (t1 - t0))
+        }
+        _ => (0.0, 0.0),
+    };
+    Ok(make_array(vec![Value::Float(vx), Value::Float(vy)]))
+}
+
+fn glfw_set_max_events_per_window(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: max_events".into());
+    }
+
+    // -1 = illimité (par défaut) ; 0 bloquerait les fenêtres pour toujours
+    let max = args[0].as_int()?;
+    let cap = match max {
+        -1 => None,
+        n if n >= 1 => Some(n as usize),
+        _ => return Err(format!("Invalid max events per window: {} (use -1 for unlimited)", max)),
+    };
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.max_events_per_window = cap;
+    Ok(Value::Null)
+}
+
+fn glfw_get_clock_info(_: Vec<Value>) -> Result<Value, String> {
+    let guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_ref().ok_or("GLFW not initialized")?;
+    let state = &state_wrapper.0;
+
+    // Lectures consécutives sous le même verrou : les valeurs sont cohérentes entre elles
+    let glfw_time = state.context.get_time();
+    let timer_value = state.context.get_timer_value();
+    let timer_frequency = state.context.get_timer_frequency();
+    let uptime = timer_value.saturating_sub(state.init_timer_value) as f64 / timer_frequency as f64;
+
+    Ok(make_map(vec![
+        ("glfw_time", Value::Float(glfw_time)),
+        // Entiers Aegis signés : un timer brut au-delà de i64::MAX serait tronqué (jamais vu en pratique)
+        ("timer_value", Value::Integer(timer_value as i64)),
+        ("timer_frequency", Value::Integer(timer_frequency as i64)),
+        ("uptime_seconds", Value::Float(uptime)),
+    ]))
+}
+
+fn glfw_push_cursor_mode(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, mode".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let code = args[1].as_int()?;
+    let mode = cursor_mode_from_code(code)?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    let previous = *state.cursor_modes.get(&id).unwrap_or(&CURSOR_NORMAL);
+    state.cursor_mode_stacks.entry(id).or_default().push(previous);
+
+    // Comme glfw_set_cursor_mode
+    window.set_cursor_mode(mode);
+    state.cursor_modes.insert(id, code);
+    state.input.cursor_last.remove(&id);
+    Ok(Value::Null)
+}
+
+fn glfw_pop_cursor_mode(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    let code = state.cursor_mode_stacks.get_mut(&id).and_then(|stack| stack.pop())
+        .ok_or(format!("Cursor mode stack is empty for window {}", id))?;
+
+    // Les codes empilés ont été validés (ou lus) au moment du push
+    window.set_cursor_mode(cursor_mode_from_code(code)?);
+    state.cursor_modes.insert(id, code);
+    state.input.cursor_last.remove(&id);
+    // Le mode restauré est renvoyé, pratique pour le débogage
+    Ok(Value::Integer(code))
+}
+
+fn glfw_get_connected_joysticks(_: Vec<Value>) -> Result<Value, String> {
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // is_gamepad n'existe qu'à partir de GLFW 3.3 : false avant
+    let gamepad = feature_supported("gamepad").unwrap_or(false);
+    let mut joysticks = Vec::new();
+    // Slots GLFW_JOYSTICK_1 à GLFW_JOYSTICK_16, dans l'ordre ; les vides sont sautés
+    for jid in 0..16 {
+        let joystick = state.context.get_joystick(joystick_id(jid)?);
+        if !joystick.is_present() {
+            continue;
+        }
+        joysticks.push(make_map(vec![
+            ("jid", Value::Integer(jid)),
+            ("name", joystick.get_name().map(Value::Str).unwrap_or(Value::Null)),
+            ("is_gamepad", Value::Boolean(gamepad && joystick.is_gamepad())),
+            ("guid", joystick.get_guid().map(Value::Str).unwrap_or(Value::Null)),
+        ]));
+    }
+    Ok(make_array(joysticks))
+}
+
+fn glfw_set_window_pos_safe(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 {
+        return Err("Args: win_id, x, y".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let x = args[1].as_int()? as i32;
+    let y = args[2].as_int()? as i32;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+    let workareas: Vec<(i32, i32, i32, i32)> = monitor_rects(state).into_iter().map(|r| r.workarea).collect();
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+    // (x, y) est le coin de la zone client : la barre de titre est au-dessus, dans le cadre
+    let (w, _) = window.get_size();
+    let (left, top, right, _) = window.get_frame_size();
+    let frame_w = w + left + right;
+    // Fenêtre sans décoration : la première ligne de pixels tient lieu de barre de titre
+    let title_h = top.max(1);
+    let visible = SAFE_TITLE_VISIBLE.min(frame_w);
+
+    // Position de la barre de titre ramenée dans une zone de travail (distance de Manhattan)
+    let clamp_into = |(wx, wy, ww, wh): (i32, i32, i32, i32), tx: i32, ty: i32| {
+        let cx = tx.clamp(wx - frame_w + visible, (wx + ww - visible).max(wx - frame_w + visible));
+        let cy = ty.clamp(wy, (wy + wh - title_h).max(wy));
+        (cx, cy, (cx - tx).abs() + (cy - ty).abs())
+    };
+
+    // Déjà attrapable sur un écran : position conservée telle quelle. Sans aucun écran, rien à borner.
+    let (tx, ty) = (x - left, y - top);
+    let (sx, sy) = workareas
+        .iter()
+        .map(|area| clamp_into(*area, tx, ty))
+        .min_by_key(|(_, _, distance)| *distance)
+        .map_or((x, y), |(cx, cy, _)| (cx + left, cy + top));
+    window.set_pos(sx, sy);
+
+    Ok(make_array(vec![Value::Integer(sx as i64), Value::Integer(sy as i64)]))
+}
+
+// Les trois fonctions de version marchent sans glfw_init (lecture de la bibliothèque chargée)
+fn glfw_get_version(_: Vec<Value>) -> Result<Value, String> {
+    let version = glfw::get_version();
+    Ok(version_map(version.major, version.minor, version.patch))
+}
+
+fn glfw_get_version_compiled(_: Vec<Value>) -> Result<Value, String> {
+    let (major, minor, revision) = compiled_version();
+    Ok(version_map(major, minor, revision))
+}
+
+fn glfw_version_mismatch(_: Vec<Value>) -> Result<Value, String> {
+    // La révision ne change ni l'API ni les fonctionnalités : seules majeure et mineure comptent
+    let runtime = glfw::get_version();
+    let (major, minor, _) = compiled_version();
+    Ok(Value::Boolean((runtime.major, runtime.minor) != (major, minor)))
+}
+
+fn glfw_set_fps_sample_window(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: frames".into());
+    }
+
+    let frames = args[0].as_int()?;
+    if frames < 1 {
+        return Err(format!("Invalid FPS sample window: {} (must be >= 1)", frames));
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // Appliqué tout de suite à la moyenne (FpsCounter::average), au prochain swap au stockage
+    state.fps_sample_window = frames as usize;
+    Ok(Value::Null)
+}
+
+fn glfw_get_fps(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_ref().ok_or("GLFW not initialized")?;
+    let state = &state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    // Lecture seule : les images sont comptées par glfw_swap_buffers
+    let fps = state.fps_counters.get(&id).map_or(0.0, |counter| counter.average(state.fps_sample_window));
+    Ok(Value::Float(fps))
+}
+
+fn glfw_set_event_key_names(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: enabled".into());
+    }
+
+    let enabled = value_as_bool(&args[0])?;
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    state.event_key_names = enabled;
+    Ok(Value::Null)
+}
+
+fn glfw_set_close_grace(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err("Args: win_id, seconds".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let seconds = value_as_float(&args[1])?;
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(format!("Invalid close grace: {} (must be finite and >= 0)", seconds));
+    }
+
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    if !state.windows.contains_key(&id) {
+        return Err(format!("Unknown window id: {}", id));
+    }
+
+    // 0 : fermeture immédiate, comme sans délai ; une demande en attente est oubliée
+    state.close_requests.remove(&id);
+    if seconds == 0.0 {
+        state.close_graces.remove(&id);
+    } else {
+        state.close_graces.insert(id, seconds);
+    }
+    Ok(Value::Null)
+}
+
+fn glfw_confirm_close(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+
+    // Sans demande en attente, rien à confirmer : false, et should_close reste inchangé
+    if state.close_requests.remove(&id).is_none() {
+        return Ok(Value::Boolean(false));
+    }
+    window.set_should_close(true);
+    Ok(Value::Boolean(true))
+}
+
+fn glfw_decode_mods(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: mods".into());
+    }
+
+    // Pur calcul sur les bits : utilisable sans glfw_init
+    let bits = args[0].as_int()?;
+    Ok(make_map(MOD_BITS.iter().map(|(name, bit)| (*name, Value::Boolean(bits & bit != 0))).collect()))
+}
+
+fn glfw_make_context_current(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: win_id".into());
+    }
+
+    let id = args[0].as_int()? as usize;
+    let mut guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_mut().ok_or("GLFW not initialized")?;
+    let state = &mut state_wrapper.0;
+
+    // 0 : aucun contexte courant (utile avant de passer le contexte à un autre thread)
+    if id == 0 {
+        glfw::make_context_current(None);
+    } else {
+        let (window, _) = state.windows.get_mut(&id).ok_or(format!("Unknown window id: {}", id))?;
+        if window.get_client_api() == glfw::ffi::NO_API {
+            return Err(format!("Cannot make context current: window {} has no OpenGL context (NO_API)", id));
+        }
+        window.make_current();
+    }
+    state.current_context = id;
+    Ok(Value::Null)
+}
+
+fn glfw_get_current_context(_: Vec<Value>) -> Result<Value, String> {
+    let guard = STATE.lock().unwrap();
+    let state_wrapper = guard.as_ref().ok_or("GLFW not initialized")?;
+    let state = &state_wrapper.0;
+
+    Ok(Value::Integer(state.current_context as i64))
+}
+
+fn glfw_event_type_code(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err("Args: type_name".into());
+    }
+
+    let name = args[0].as_str()?;
+    EVENT_TYPE_CODES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, code)| Value::Integer(*code))
+        .ok_or(format!("Unknown event type: {}", name))
+}
+
+// Toutes les valeurs sont lues dans les tables utilisées par les conversions : elles ne peuvent pas diverger
+fn glfw_constants(_: Vec<Value>) -> Result<Value, String> {
+    let mut constants: HashMap<String, Value> = HashMap::new();
+    let mut add = |prefix: &str, table: &[(&str, i64)]| {
+        for (name, code) in table {
+            constants.insert(format!("{}{}", prefix, name.to_uppercase()), Value::Integer(*code));
+        }
+    };
+
+    let keys: Vec<(&str, i64)> = KEY_CODES.iter().map(|(name, code)| (*name, *code as i64)).collect();
+    let buttons: Vec<(&str, i64)> = MOUSE_BUTTON_CODES.iter().map(|(name, code)| (*name, *code as i64)).collect();
+    add("KEY_", &keys);
+    add("MOUSE_BUTTON_", &buttons);
+    add("", ACTION_CODES);
+    add("EVENT_", EVENT_TYPE_CODES);
+    add("EVENT_FILTER_", EVENT_FILTER_CODES);
+    add("MOD_", MOD_BITS);
+    add("HINT_", WINDOW_HINT_CODES);
+    add("INIT_HINT_", INIT_HINT_CODES);
+    add("INPUT_MODE_", INPUT_MODE_CODES);
+    add("CURSOR_", CURSOR_MODE_CODES);
+    add("PLATFORM_", PLATFORM_CODES);
+    add("", &[("ANY_POSITION", ANY_POSITION)]);
+
+    Ok(Value::Map(Rc::new(RefCell::new(constants))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // STATE est global : les tests qui initialisent GLFW passent un par un
+    static GLFW_LOCK: Mutex<()> = Mutex::new(());
+
+    const PLATFORM_NULL: i64 = 0x00060005;
+
+    // GLFW initialisé pour la durée d'un test, terminé au drop (même en cas d'échec)
+    struct Session {
+        _serial: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl Session {
+        fn start() -> Session {
+            let serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            // Plateforme NULL si le runtime est en 3.4 : aucun affichage requis
+            let _ = glfw_init_hint(vec![Value::Integer(INIT_HINT_PLATFORM), Value::Integer(PLATFORM_NULL)]);
+            glfw_init(vec![]).unwrap();
+            Session { _serial: serial }
+        }
+
+        // Fenêtre cachée sans contexte OpenGL, le seul type que toutes les plateformes savent créer
+        fn window(&self) -> usize {
+            self.window_with(vec![])
+        }
+
+        // Idem, avec des hints supplémentaires (nom sans préfixe, valeur)
+        fn window_with(&self, hints: Vec<(&str, Value)>) -> usize {
+            glfw_window_hint(vec![Value::Integer(hint_code("CLIENT_API")), Value::Integer(0)]).unwrap();
+            glfw_window_hint(vec![Value::Integer(hint_code("VISIBLE")), Value::Boolean(false)]).unwrap();
+            for (name, value) in hints {
+                glfw_window_hint(vec![Value::Integer(hint_code(name)), value]).unwrap();
+            }
+            let id = glfw_create_window(vec![Value::Integer(64), Value::Integer(48), Value::Str("test".into())]).unwrap();
+            int(id) as usize
+        }
+    }
+
+    impl Drop for Session {
+        fn drop(&mut self) {
+            let _ = glfw_terminate(vec![]);
+        }
+    }
+
+    fn hint_code(name: &str) -> i64 {
+        WINDOW_HINT_CODES.iter().find(|(n, _)| *n == name).map(|(_, c)| *c).unwrap()
+    }
+
+    fn int(value: Value) -> i64 {
+        match value {
+            Value::Integer(i) => i,
+            _ => panic!("expected an Integer"),
+        }
+    }
+
+    fn float(value: Value) -> f64 {
+        match value {
+            Value::Float(f) => f,
+            _ => panic!("expected a Float"),
+        }
+    }
+
+    fn error(result: Result<Value, String>) -> String {
+        match result {
+            Err(e) => e,
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    // Action sur la touche A de la fenêtre 1 pendant un pompage
+    fn press(frame: &mut FrameInput, action: glfw::Action) {
+        frame.key_edges.entry(1).or_default().record(action, glfw::Key::A as i32);
+    }
+
+    fn just(frame: &FrameInput) -> (bool, bool) {
+        let edges = frame.key_edges.get(&1);
+        (
+            edges.is_some_and(|e| e.pressed.contains(&(glfw::Key::A as i32))),
+            edges.is_some_and(|e| e.released.contains(&(glfw::Key::A as i32))),
+        )
+    }
+
+    #[test]
+    fn tap_within_one_poll_reports_both_edges() {
+        let mut frame = FrameInput::default();
+        frame.begin_pump();
+        press(&mut frame, glfw::Action::Press);
+        press(&mut frame, glfw::Action::Release);
+        frame.end_poll();
+        assert_eq!(just(&frame), (true, true));
+
+        // Frame suivante sans événement : plus rien
+        frame.begin_pump();
+        frame.end_poll();
+        assert_eq!(just(&frame), (false, false));
+    }
+
+    #[test]
+    fn held_key_is_pressed_on_its_first_frame_only() {
+        let mut frame = FrameInput::default();
+        frame.begin_pump();
+        press(&mut frame, glfw::Action::Press);
+        frame.end_poll();
+        assert_eq!(just(&frame), (true, false));
+
+        frame.begin_pump();
+        frame.end_poll();
+        assert_eq!(just(&frame), (false, false));
+    }
+
+    #[test]
+    fn os_repeat_is_not_an_edge() {
+        let mut frame = FrameInput::default();
+        frame.begin_pump();
+        press(&mut frame, glfw::Action::Repeat);
+        frame.end_poll();
+        assert_eq!(just(&frame), (false, false));
+    }
+
+    #[test]
+    fn auto_poll_between_frames_does_not_wipe_edges() {
+        let mut frame = FrameInput::default();
+        frame.begin_pump();
+        frame.end_poll();
+
+        // glfw_window_should_close pompe l'appui, puis glfw_poll_events ne trouve plus rien
+        frame.begin_pump();
+        press(&mut frame, glfw::Action::Press);
+        frame.begin_pump();
+        frame.end_poll();
+        assert_eq!(just(&frame), (true, false));
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn no_api_window_leaves_current_context_alone() {
+        let session = Session::start();
+        let id = session.window();
+        assert_eq!(int(glfw_get_current_context(vec![]).unwrap()), 0);
+
+        let err = error(glfw_make_context_current(vec![Value::Integer(id as i64)]));
+        assert!(err.contains("NO_API"), "{}", err);
+        assert_eq!(int(glfw_get_current_context(vec![]).unwrap()), 0);
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn terminate_then_init_starts_from_empty_registries() {
+        {
+            let session = Session::start();
+            let id = session.window();
+            glfw_set_window_tag(vec![Value::Integer(id as i64), Value::Str("player".into())]).unwrap();
+            glfw_post_user_event(vec![Value::Integer(7)]).unwrap();
+        }
+
+        let _session = Session::start();
+        assert!(USER_EVENTS.lock().unwrap().is_empty());
+        let guard = STATE.lock().unwrap();
+        let state = &guard.as_ref().unwrap().0;
+        assert!(state.windows.is_empty());
+        assert!(state.window_tags.is_empty());
+        assert!(state.window_callbacks.is_empty());
+        assert!(state.callback_queue.is_empty());
+        assert!(state.event_queue.is_empty());
+        assert!(state.pending_events.is_empty());
+    }
+
+    #[test]
+    fn terminate_without_init_is_a_no_op() {
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(matches!(glfw_terminate(vec![]), Ok(Value::Null)));
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn terminate_from_another_thread_is_an_error() {
+        let _session = Session::start();
+        let result = std::thread::spawn(|| glfw_terminate(vec![]).err()).join().unwrap();
+        assert!(result.is_some_and(|e| e.contains("thread that called glfw_init")));
+        // Toujours initialisé : le thread principal peut encore créer des fenêtres
+        assert!(STATE.lock().unwrap().is_some());
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn poll_fills_the_event_queue_before_next_event_is_first_called() {
+        let _session = Session::start();
+        // Deux polls avant tout nextEvent : rien n'est perdu, l'ordre est conservé
+        glfw_post_user_event(vec![Value::Integer(1)]).unwrap();
+        glfw_poll_events(vec![]).unwrap();
+        glfw_post_user_event(vec![Value::Integer(2)]).unwrap();
+        glfw_poll_events(vec![]).unwrap();
+        for expected in [1, 2] {
+            let event = glfw_next_event(vec![]).unwrap();
+            assert!(matches!(map_field(&event, "type").unwrap(), Value::Str(kind) if kind == "user"));
+            assert_eq!(int(map_field(&event, "payload").unwrap()), expected);
+        }
+        assert!(matches!(glfw_next_event(vec![]).unwrap(), Value::Null));
+    }
+
+    #[test]
+    fn clipboard_string_with_nul_is_rejected_before_reaching_glfw() {
+        let err = error(glfw_set_clipboard_string(vec![Value::Str("ab\0cd".into())]));
+        assert!(err.contains("NUL byte at index 2"), "{}", err);
+        let err = error(glfw_set_clipboard_string(vec![Value::Integer(1), Value::Str("\0".into())]));
+        assert!(err.contains("NUL byte at index 0"), "{}", err);
+    }
+
+    #[test]
+    fn window_title_with_nul_is_rejected_before_reaching_glfw() {
+        let err = error(glfw_set_window_title(vec![Value::Integer(1), Value::Str("Game\0".into())]));
+        assert!(err.contains("NUL byte at index 4"), "{}", err);
+        let err = error(glfw_set_window_title_fps(vec![Value::Integer(1), Value::Str("\0fps".into())]));
+        assert!(err.contains("NUL byte at index 0"), "{}", err);
+    }
+
+    #[test]
+    fn set_time_rejects_nan_infinite_and_negative_values() {
+        for time in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -1.0] {
+            let err = error(glfw_set_time(vec![Value::Float(time)]));
+            assert!(err.starts_with("Invalid time"), "{}: {}", time, err);
+        }
+        let err = error(glfw_set_time(vec![Value::Float(MAX_GLFW_TIME * 2.0)]));
+        assert!(err.contains("must be <="), "{}", err);
+    }
+
+    #[test]
+    fn wait_events_timeout_rejects_negative_and_nan() {
+        for timeout in [-1.0, f64::NAN, f64::INFINITY] {
+            let err = error(glfw_wait_events_timeout(vec![Value::Float(timeout)]));
+            assert!(err.starts_with("Timeout must be a finite number"), "{}: {}", timeout, err);
+        }
+    }
+
+    fn limits(min_width: i64, min_height: i64, max_width: i64, max_height: i64) -> SizeLimits {
+        SizeLimits {
+            min_width: size_limit(min_width),
+            min_height: size_limit(min_height),
+            max_width: size_limit(max_width),
+            max_height: size_limit(max_height),
+        }
+    }
+
+    #[test]
+    fn size_limits_reject_min_greater_than_max() {
+        let err = limits(800, 100, 640, 480).validate().unwrap_err();
+        assert!(err.contains("min width 800 is greater than max width 640"), "{}", err);
+        let err = limits(100, 600, 640, 480).validate().unwrap_err();
+        assert!(err.contains("min height 600 is greater than max height 480"), "{}", err);
+        assert!(limits(640, 480, 640, 480).validate().is_ok());
+    }
+
+    #[test]
+    fn size_limits_with_a_dont_care_side_are_not_compared() {
+        assert!(limits(800, -1, 640, 480).validate().is_err());
+        assert!(limits(800, 600, -1, 480).validate().is_err());
+        assert!(limits(800, 600, -1, -1).validate().is_ok());
+        assert!(limits(-1, -1, 640, 480).validate().is_ok());
+        assert!(limits(-1, 600, 640, -1).validate().is_ok());
+    }
+
+    #[test]
+    fn mod_bits_match_the_glfw_layout() {
+        let glfw_bits = [
+            ("shift", glfw::Modifiers::Shift),
+            ("control", glfw::Modifiers::Control),
+            ("alt", glfw::Modifiers::Alt),
+            ("super", glfw::Modifiers::Super),
+            ("caps_lock", glfw::Modifiers::CapsLock),
+            ("num_lock", glfw::Modifiers::NumLock),
+        ];
+        for (name, modifier) in glfw_bits {
+            let bit = MOD_BITS.iter().find(|(n, _)| *n == name).map(|(_, b)| *b).unwrap();
+            assert_eq!(bit, modifier.bits() as i64, "{}", name);
+            assert_eq!(mods_code(modifier), bit, "{}", name);
+        }
+    }
+
+    #[test]
+    fn unknown_mod_bits_are_masked() {
+        assert_eq!(mods_mask(0x40 | 0x0001), 0x0001);
+        assert_eq!(mods_mask(!0), 0x003f);
+
+        let decoded = glfw_decode_mods(vec![Value::Integer(0x0002 | 0x0020 | 0x0100)]).unwrap();
+        let Value::Map(map) = &decoded else { panic!("expected a map") };
+        assert_eq!(map.borrow().len(), MOD_BITS.len());
+        for (name, _) in MOD_BITS {
+            let set = matches!(map_field(&decoded, name).unwrap(), Value::Boolean(true));
+            assert_eq!(set, *name == "control" || *name == "num_lock", "{}", name);
+        }
+    }
+
+    #[test]
+    fn base64_round_trips_every_tail_length() {
+        for (bytes, text) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\0\xff\x80", "AP+A"),
+        ] {
+            assert_eq!(base64_encode(bytes), text);
+            assert_eq!(base64_decode(text).as_deref(), Some(bytes), "{}", text);
+        }
+        assert_eq!(base64_decode(" Zm8=\n").as_deref(), Some(&b"fo"[..]));
+    }
+
+    #[test]
+    fn base64_rejects_invalid_input() {
+        for text in ["A=AA", "Zg=", "Zg", "Zg==Zg==", "Z===", "====", "Zm9v!A==", "Zm-v"] {
+            assert!(base64_decode(text).is_none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn fps_average_is_read_only_and_follows_recorded_frames() {
+        let mut counter = FpsCounter::default();
+        assert_eq!(counter.average(FPS_SAMPLES), 0.0);
+        counter.record(1.0, FPS_SAMPLES);
+        assert_eq!(counter.average(FPS_SAMPLES), 0.0);
+
+        counter.record(1.5, FPS_SAMPLES);
+        counter.record(1.75, FPS_SAMPLES);
+        // Deux intervalles (0.5 + 0.25 s) : 2 / 0.75
+        let fps = counter.average(FPS_SAMPLES);
+        assert!((fps - 2.0 / 0.75).abs() < 1e-9, "{}", fps);
+        assert_eq!(counter.average(FPS_SAMPLES), fps);
+    }
+
+    #[test]
+    fn fps_sample_window_limits_the_average() {
+        let mut counter = FpsCounter::default();
+        for now in [0.0, 1.0, 1.5, 1.75] {
+            counter.record(now, 2);
+        }
+        assert_eq!(counter.deltas.len(), 2);
+        // Fenêtre réduite après coup : seul le dernier intervalle (0.25 s) compte
+        assert!((counter.average(1) - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn proc_names_with_nul_are_rejected_before_reaching_glfw() {
+        let names = make_array(vec![Value::Str("glClear".into()), Value::Str("glClear\0Color".into())]);
+        let err = error(glfw_get_proc_addresses(vec![names]));
+        assert!(err.contains("contains a NUL byte"), "{}", err);
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn window_opacity_round_trips_where_supported() {
+        let session = Session::start();
+        let id = session.window_with(vec![("TRANSPARENT_FRAMEBUFFER", Value::Boolean(true))]) as i64;
+        if glfw_set_window_opacity(vec![Value::Integer(id), Value::Float(0.5)]).is_err() {
+            return; // Plateforme sans transparence : voir opaque_window_reports_full_opacity
+        }
+        let opacity = float(glfw_get_window_opacity(vec![Value::Integer(id)]).unwrap());
+        assert!((opacity - 0.5).abs() < 0.01, "{}", opacity);
+
+        // Hors de [0, 1] : ramené aux bornes
+        glfw_set_window_opacity(vec![Value::Integer(id), Value::Float(3.0)]).unwrap();
+        assert_eq!(float(glfw_get_window_opacity(vec![Value::Integer(id)]).unwrap()), 1.0);
+    }
+
+    #[test]
+    fn fade_rejects_invalid_durations_before_reaching_glfw() {
+        for seconds in [-0.5, f64::NAN, f64::INFINITY] {
+            let err = error(glfw_fade_window(vec![Value::Integer(1), Value::Float(0.0), Value::Float(seconds)]));
+            assert!(err.starts_with("Invalid fade duration"), "{}: {}", seconds, err);
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn zero_length_fade_applies_immediately_where_supported() {
+        let session = Session::start();
+        let id = session.window_with(vec![("TRANSPARENT_FRAMEBUFFER", Value::Boolean(true))]) as i64;
+        let fade = glfw_fade_window(vec![Value::Integer(id), Value::Float(0.25), Value::Float(0.0)]);
+        if fade.is_err() {
+            return;
+        }
+        let opacity = float(glfw_get_window_opacity(vec![Value::Integer(id)]).unwrap());
+        assert!((opacity - 0.25).abs() < 0.01, "{}", opacity);
+
+        let err = error(glfw_fade_window(vec![Value::Integer(id + 1), Value::Float(1.0), Value::Float(1.0)]));
+        assert!(err.starts_with("Unknown window id"), "{}", err);
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn opaque_window_reports_full_opacity() {
+        let session = Session::start();
+        let id = session.window_with(vec![("TRANSPARENT_FRAMEBUFFER", Value::Boolean(false))]) as i64;
+        assert_eq!(float(glfw_get_window_opacity(vec![Value::Integer(id)]).unwrap()), 1.0);
+        // Le setter, lui, signale l'absence de support
+        let err = error(glfw_set_window_opacity(vec![Value::Integer(id), Value::Float(0.5)]));
+        assert!(err.contains("not supported"), "{}", err);
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn swapping_a_no_api_window_is_an_error() {
+        let session = Session::start();
+        let id = session.window() as i64;
+        let err = error(glfw_swap_buffers(vec![Value::Integer(id)]));
+        assert!(err.contains("NO_API"), "{}", err);
+        let err = error(glfw_swap_buffers_timed(vec![Value::Integer(id)]));
+        assert!(err.contains("NO_API"), "{}", err);
+    }
+
+    #[test]
+    fn queued_callback_is_callback_then_argc_then_args() {
+        let mut queue = VecDeque::new();
+        queue_callback(&mut queue, &Value::Str("on_key".into()), vec![Value::Integer(1), Value::Integer(65)]);
+        let call = value_as_array(&queue.pop_front().unwrap()).unwrap();
+        assert_eq!(call.len(), 4);
+        assert!(matches!(&call[0], Value::Str(name) if name == "on_key"));
+        assert!(matches!(call[1], Value::Integer(2)));
+        assert!(matches!(call[2], Value::Integer(1)));
+        assert!(matches!(call[3], Value::Integer(65)));
+    }
+
+    #[test]
+    fn next_callback_without_init_ends_the_dispatch_loop() {
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(matches!(glfw_next_callback(vec![]), Ok(Value::Null)));
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn callback_can_reenter_the_plugin_without_deadlock() {
+        let _session = Session::start();
+        {
+            let mut guard = STATE.lock().unwrap();
+            let state = &mut guard.as_mut().unwrap().0;
+            let callback = Value::Str("on_key".into());
+            queue_callback(&mut state.callback_queue, &callback, vec![Value::Integer(1)]);
+            queue_callback(&mut state.callback_queue, &callback, vec![Value::Integer(2)]);
+        }
+
+        // Ce que fait dispatchCallbacks : le verrou est rendu avant d'appeler le callback
+        assert!(!matches!(glfw_next_callback(vec![]).unwrap(), Value::Null));
+        assert!(STATE.try_lock().is_ok());
+        glfw_get_time(vec![]).unwrap();
+        glfw_poll_events(vec![]).unwrap();
+        glfw_terminate(vec![]).unwrap();
+        // Terminé pendant le dispatch : la boucle s'arrête, le callback restant est abandonné
+        assert!(matches!(glfw_next_callback(vec![]), Ok(Value::Null)));
+    }
+
+    // Registres par fenêtre de GlfwState qui contiennent encore `id`
+    fn registries_holding(state: &GlfwState, id: usize) -> Vec<&'static str> {
+        let registries = [
+            ("windows", state.windows.contains_key(&id)),
+            ("restored_geometry", state.restored_geometry.contains_key(&id)),
+            ("window_tags", state.window_tags.contains_key(&id)),
+            ("current_mods", state.input.current_mods.contains_key(&id)),
+            ("size_limits", state.size_limits.contains_key(&id)),
+            ("close_times", state.close_times.contains_key(&id)),
+            ("window_sizes", state.window_sizes.contains_key(&id)),
+            ("window_callbacks", state.window_callbacks.contains_key(&id)),
+            ("windowed_geometry", state.windowed_geometry.contains_key(&id)),
+            ("cursor_modes", state.cursor_modes.contains_key(&id)),
+            ("last_resize_times", state.last_resize_times.contains_key(&id)),
+            ("cursor_last", state.input.cursor_last.contains_key(&id)),
+            ("cursor_deltas", state.input.cursor_deltas.contains_key(&id)),
+            ("fps_counters", state.fps_counters.contains_key(&id)),
+            ("attention_pending", state.attention_pending.contains(&id)),
+            ("dpi_autoscale", state.dpi_autoscale.contains_key(&id)),
+            ("aspect_ratios", state.aspect_ratios.contains_key(&id)),
+            ("window_titles", state.window_titles.contains_key(&id)),
+            ("ignore_next_close", state.ignore_next_close.contains(&id)),
+            ("resizable", state.resizable.contains_key(&id)),
+            ("cursor_hide_regions", state.cursor_hide_regions.contains_key(&id)),
+            ("fades", state.fades.contains_key(&id)),
+            ("held_keys", state.input.held_keys.contains_key(&id)),
+            ("cursor_samples", state.input.cursor_samples.contains_key(&id)),
+            ("cursor_mode_stacks", state.cursor_mode_stacks.contains_key(&id)),
+            ("close_graces", state.close_graces.contains_key(&id)),
+            ("close_requests", state.close_requests.contains_key(&id)),
+            ("scroll_deltas", state.frame_input.scroll_deltas.contains_key(&id)),
+            ("key_edges", state.frame_input.key_edges.contains_key(&id)),
+            ("mouse_button_edges", state.frame_input.mouse_button_edges.contains_key(&id)),
+        ];
+        registries.iter().filter(|(_, held)| *held).map(|(name, _)| *name).collect()
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn destroying_a_window_purges_every_registry() {
+        let session = Session::start();
+        let id = session.window();
+        let win = Value::Integer(id as i64);
+        glfw_set_key_callback(vec![win.clone(), Value::Str("on_key".into())]).unwrap();
+        glfw_set_window_tag(vec![win.clone(), Value::Str("player".into())]).unwrap();
+        glfw_ignore_next_close(vec![win.clone()]).unwrap();
+        glfw_set_window_size_limits(vec![win.clone(), Value::Integer(32), Value::Integer(32), Value::Integer(-1), Value::Integer(-1)]).unwrap();
+        {
+            let guard = STATE.lock().unwrap();
+            let held = registries_holding(&guard.as_ref().unwrap().0, id);
+            for registry in ["window_callbacks", "window_tags", "ignore_next_close", "size_limits", "window_titles"] {
+                assert!(held.contains(&registry), "{} not set up", registry);
+            }
+        }
+
+        glfw_destroy_window(vec![win.clone()]).unwrap();
+        let guard = STATE.lock().unwrap();
+        assert_eq!(registries_holding(&guard.as_ref().unwrap().0, id), Vec::<&str>::new());
+        drop(guard);
+        assert!(error(glfw_destroy_window(vec![win])).starts_with("Unknown window id"));
+    }
+
+    #[test]
+    fn held_key_repeats_are_distinct_key_records() {
+        let held = [glfw::Action::Press, glfw::Action::Repeat, glfw::Action::Repeat, glfw::Action::Release];
+        let actions: Vec<i64> = held
+            .iter()
+            .map(|action| {
+                let event = WindowEvent::Key(glfw::Key::Right, 0, *action, glfw::Modifiers::empty());
+                let record = window_event_record(1, 0.0, &event).unwrap();
+                assert!(matches!(map_field(&record, "type").unwrap(), Value::Str(kind) if kind == "key"));
+                int(map_field(&record, "action").unwrap())
+            })
+            .collect();
+        assert_eq!(actions, vec![1, 2, 2, 0]);
+    }
+
+    #[test]
+    fn dpi_hints_map_to_their_documented_codes() {
+        assert_eq!(window_hint_name(0x0002200C), Some("SCALE_TO_MONITOR"));
+        assert_eq!(window_hint_name(0x0002200D), Some("SCALE_FRAMEBUFFER"));
+        assert!(hint_bool(&Value::Integer(1)).unwrap());
+        assert!(!hint_bool(&Value::Boolean(false)).unwrap());
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn scale_to_monitor_window_uses_the_monitor_content_scale() {
+        let _session = Session::start();
+        for (name, value) in [("CLIENT_API", Value::Integer(0)), ("VISIBLE", Value::Boolean(false)), ("SCALE_TO_MONITOR", Value::Boolean(true))] {
+            glfw_window_hint(vec![Value::Integer(hint_code(name)), value]).unwrap();
+        }
+        let window = glfw_create_window_ex(vec![Value::Integer(200), Value::Integer(100), Value::Str("dpi".into())]).unwrap();
+        let primary = value_as_array(&glfw_get_primary_content_scale(vec![]).unwrap()).unwrap();
+        // Fenêtre créée sur le moniteur principal : même échelle que lui
+        assert_eq!(float(map_field(&window, "scale_x").unwrap()), float(primary[0].clone()));
+        assert_eq!(float(map_field(&window, "scale_y").unwrap()), float(primary[1].clone()));
+    }
+
+    #[test]
+    fn context_hints_map_to_their_documented_codes() {
+        assert_eq!(window_hint_name(0x00022009), Some("CONTEXT_RELEASE_BEHAVIOR"));
+        assert_eq!(window_hint_name(0x0002200A), Some("CONTEXT_NO_ERROR"));
+        assert_eq!(window_hint_name(0x0002200B), Some("CONTEXT_CREATION_API"));
+    }
+
+    #[test]
+    #[ignore = "requires a display with an OpenGL driver"]
+    fn no_error_context_window_can_be_created() {
+        let _session = Session::start();
+        let hint = |name: &str, value: Value| glfw_window_hint(vec![Value::Integer(hint_code(name)), value]);
+        assert!(error(hint("CONTEXT_RELEASE_BEHAVIOR", Value::Integer(0x1234))).starts_with("Invalid CONTEXT_RELEASE_BEHAVIOR"));
+        assert!(error(hint("CONTEXT_CREATION_API", Value::Integer(0))).starts_with("Invalid CONTEXT_CREATION_API"));
+
+        hint("VISIBLE", Value::Boolean(false)).unwrap();
+        hint("CONTEXT_NO_ERROR", Value::Boolean(true)).unwrap();
+        hint("CONTEXT_RELEASE_BEHAVIOR", Value::Integer(0x00035002)).unwrap();
+        hint("CONTEXT_CREATION_API", Value::Integer(0x00036001)).unwrap();
+        let id = int(glfw_create_window(vec![Value::Integer(64), Value::Integer(48), Value::Str("no error".into())]).unwrap());
+        assert!(id > 0);
+    }
+
+    #[test]
+    fn cursor_mode_codes_round_trip() {
+        for code in [CURSOR_NORMAL, CURSOR_HIDDEN, CURSOR_DISABLED] {
+            assert!(cursor_mode_from_code(code).is_ok());
+        }
+        assert!(matches!(cursor_mode_from_code(CURSOR_DISABLED), Ok(glfw::CursorMode::Disabled)));
+        assert!(cursor_mode_from_code(0x00034009).is_err());
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn set_cursor_pos_is_refused_while_disabled() {
+        let session = Session::start();
+        let win = Value::Integer(session.window() as i64);
+        let move_cursor = || glfw_set_cursor_pos(vec![win.clone(), Value::Float(10.0), Value::Float(10.0)]);
+        move_cursor().unwrap();
+
+        glfw_set_cursor_mode(vec![win.clone(), Value::Integer(CURSOR_DISABLED)]).unwrap();
+        assert!(error(move_cursor()).contains("DISABLED"));
+
+        glfw_set_cursor_mode(vec![win.clone(), Value::Integer(CURSOR_NORMAL)]).unwrap();
+        move_cursor().unwrap();
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn primary_monitor_is_zero_only_without_monitors() {
+        let _session = Session::start();
+        let primary = int(glfw_get_primary_monitor(vec![]).unwrap());
+        let monitors = value_as_array(&glfw_get_monitors(vec![]).unwrap()).unwrap();
+        let ids: Vec<i64> = monitors.iter().map(|m| int(map_field(m, "id").unwrap())).collect();
+        if ids.is_empty() {
+            assert_eq!(primary, 0);
+        } else {
+            assert!(ids.contains(&primary));
+        }
+
+        // 0 n'est jamais un id valide : les fonctions qui prennent un moniteur échouent proprement
+        let err = error(glfw_match_video_mode(vec![Value::Integer(0), Value::Integer(640), Value::Integer(480), Value::Integer(60)]));
+        assert_eq!(err, "Unknown monitor id: 0");
+    }
+
+    #[test]
+    fn aspect_ratio_rejects_partial_or_non_positive_ratios() {
+        for (numer, denom) in [(0, 9), (16, 0), (-1, 9), (16, -1), (-2, -2)] {
+            let err = error(glfw_set_window_aspect_ratio(vec![Value::Integer(1), Value::Integer(numer), Value::Integer(denom)]));
+            assert!(err.starts_with("Invalid aspect ratio"), "{}:{}: {}", numer, denom, err);
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn aspect_ratio_can_be_locked_then_unlocked() {
+        let session = Session::start();
+        let win = Value::Integer(session.window() as i64);
+        let resize = || {
+            let size = value_as_array(&glfw_set_window_size(vec![win.clone(), Value::Integer(320), Value::Integer(320)]).unwrap()).unwrap();
+            (int(size[0].clone()), int(size[1].clone()))
+        };
+
+        glfw_set_window_aspect_ratio(vec![win.clone(), Value::Integer(16), Value::Integer(9)]).unwrap();
+        assert_eq!(resize(), (320, 180));
+
+        glfw_set_window_aspect_ratio(vec![win.clone(), Value::Integer(-1), Value::Integer(-1)]).unwrap();
+        assert!(matches!(glfw_get_window_aspect_ratio(vec![win.clone()]).unwrap(), Value::Null));
+        assert_eq!(resize(), (320, 320));
+
+        let err = error(glfw_set_window_aspect_ratio(vec![Value::Integer(999), Value::Integer(16), Value::Integer(9)]));
+        assert!(err.starts_with("Unknown window id"), "{}", err);
+    }
+
+    #[test]
+    fn init_failure_message_names_the_common_causes() {
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let with_error = |error: Option<(i32, &str)>| {
+            *LAST_ERROR.lock().unwrap() = error.map(|(code, text)| (code, text.to_string()));
+            init_failure_message("fallback")
+        };
+
+        assert_eq!(with_error(None), "GLFW Init Error: fallback");
+        assert!(with_error(Some((ERROR_PLATFORM_ERROR, "X11: Failed to open display"))).contains("no display available"));
+        assert!(with_error(Some((ERROR_PLATFORM_ERROR, "Wayland: Failed to connect to display"))).contains("no display available"));
+        assert!(with_error(Some((ERROR_PLATFORM_UNAVAILABLE, "Win32: not supported"))).contains("platform unavailable"));
+        assert!(with_error(Some((ERROR_API_UNAVAILABLE, "no GL"))).contains("graphics API unavailable"));
+        assert!(with_error(Some((ERROR_PLATFORM_ERROR, "boom"))).contains("platform error (boom)"));
+        *LAST_ERROR.lock().unwrap() = None;
+    }
+
+    #[test]
+    #[ignore = "requires GLFW 3.4 with the NULL platform"]
+    fn init_failure_is_a_recoverable_error() {
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        // Plateforme absente de ce système : glfwInit échoue sans tuer le processus
+        let unavailable = if cfg!(target_os = "windows") { 0x00060004 } else { 0x00060001 };
+        glfw_init_hint(vec![Value::Integer(INIT_HINT_PLATFORM), Value::Integer(unavailable)]).unwrap();
+        let err = error(glfw_init(vec![]));
+        assert!(err.starts_with("GLFW Init Error"), "{}", err);
+        assert!(STATE.lock().unwrap().is_none());
+
+        // Et l'init suivante, sur la plateforme NULL, réussit
+        glfw_init_hint(vec![Value::Integer(INIT_HINT_PLATFORM), Value::Integer(PLATFORM_NULL)]).unwrap();
+        glfw_init(vec![]).unwrap();
+        glfw_terminate(vec![]).unwrap();
+    }
+
+    #[test]
+    fn clipboard_truncation_keeps_whole_characters() {
+        let text = "0123456789abcdef".as_bytes();
+        assert_eq!(truncate_at_char_boundary(text, 10), b"0123456789");
+        assert_eq!(truncate_at_char_boundary(text, 100), text);
+
+        // "é" occupe les octets 9 et 10 : une limite de 10 l'exclut en entier
+        let accented = "123456789é!".as_bytes();
+        assert_eq!(truncate_at_char_boundary(accented, 10), b"123456789");
+        assert_eq!(truncate_at_char_boundary(accented, 11), "123456789é".as_bytes());
+        assert_eq!(truncate_at_char_boundary("é".as_bytes(), 1), b"");
+        assert_eq!(truncate_at_char_boundary(text, 0), b"");
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn clipboard_read_limit_truncates_longer_strings() {
+        let _session = Session::start();
+        glfw_set_clipboard_string(vec![Value::Str("0123456789 and then some".into())]).unwrap();
+        glfw_set_clipboard_read_limit(vec![Value::Integer(10)]).unwrap();
+        assert!(matches!(glfw_get_clipboard_string(vec![]).unwrap(), Value::Str(text) if text == "0123456789"));
+
+        glfw_set_clipboard_read_limit(vec![Value::Integer(-1)]).unwrap();
+        assert!(matches!(glfw_get_clipboard_string(vec![]).unwrap(), Value::Str(text) if text.len() == 24));
+    }
+
+    #[test]
+    fn position_hint_values() {
+        assert_eq!(position_hint_value(&Value::Integer(100)).unwrap(), Some(100));
+        assert_eq!(position_hint_value(&Value::Integer(-20)).unwrap(), Some(-20));
+        assert_eq!(position_hint_value(&Value::Integer(ANY_POSITION)).unwrap(), None);
+        assert!(position_hint_value(&Value::Integer(i64::from(i32::MIN) - 1)).is_err());
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn window_is_created_at_the_position_hint() {
+        let session = Session::start();
+        let id = session.window_with(vec![("POSITION_X", Value::Integer(100)), ("POSITION_Y", Value::Integer(100))]);
+        let guard = STATE.lock().unwrap();
+        let (window, _) = &guard.as_ref().unwrap().0.windows[&id];
+        let (x, y) = window.get_pos();
+        // Le gestionnaire de fenêtres peut décaler de la taille des décorations
+        assert!((x - 100).abs() <= 64 && (y - 100).abs() <= 64, "({}, {})", x, y);
+    }
+
+    #[test]
+    fn attribs_newer_than_the_runtime_are_refused() {
+        let err = check_attrib_version("HOVERED", (3, 2)).unwrap_err();
+        assert_eq!(err, "Window attrib HOVERED unsupported on this GLFW version (3.2)");
+        assert!(check_attrib_version("HOVERED", (3, 3)).is_ok());
+        assert!(check_attrib_version("MOUSE_PASSTHROUGH", (3, 3)).is_err());
+        assert!(check_attrib_version("MOUSE_PASSTHROUGH", (3, 4)).is_ok());
+        // Attributs de GLFW 3.2 : jamais refusés
+        assert!(check_attrib_version("RESIZABLE", (3, 2)).is_ok());
+    }
+
+    #[test]
+    fn opacity_and_content_scale_need_glfw_3_3() {
+        for feature in ["window_opacity", "content_scale"] {
+            assert_eq!(feature_supported_by(feature, (3, 2)), Some(false), "{}", feature);
+            assert_eq!(feature_supported_by(feature, (3, 3)), Some(true), "{}", feature);
+        }
+        assert_eq!(feature_supported_by("no_such_feature", (3, 4)), None);
+    }
+
+    #[test]
+    fn tool_window_hints_map_to_their_documented_codes() {
+        assert_eq!(window_hint_name(0x00020001), Some("FOCUSED"));
+        assert_eq!(window_hint_name(0x00020006), Some("AUTO_ICONIFY"));
+        assert_eq!(window_hint_name(0x00020007), Some("FLOATING"));
+        assert_eq!(window_hint_name(0x0002000C), Some("FOCUS_ON_SHOW"));
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn floating_unfocused_window_reports_its_attribs() {
+        let session = Session::start();
+        let id = session.window_with(vec![("FLOATING", Value::Boolean(true)), ("FOCUSED", Value::Boolean(false))]);
+        let attrib = |name: &str| glfw_get_window_attrib(vec![Value::Integer(id as i64), Value::Integer(hint_code(name))]).unwrap();
+        assert!(matches!(attrib("FLOATING"), Value::Boolean(true)));
+        assert!(matches!(attrib("FOCUSED"), Value::Boolean(false)));
+    }
+
+    #[test]
+    fn cocoa_init_hints_are_accepted_on_every_platform() {
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for hint in [INIT_HINT_COCOA_MENUBAR, INIT_HINT_COCOA_CHDIR_RESOURCES] {
+            assert!(glfw_init_hint(vec![Value::Integer(hint), Value::Boolean(true)]).is_ok(), "{:#x}", hint);
+        }
+        assert!(error(glfw_init_hint(vec![Value::Integer(0x00051009), Value::Boolean(true)])).starts_with("Unknown init hint"));
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    #[ignore = "requires a display"]
+    fn init_succeeds_without_a_menu_bar() {
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        glfw_init_hint(vec![Value::Integer(INIT_HINT_COCOA_MENUBAR), Value::Boolean(false)]).unwrap();
+        glfw_init_hint(vec![Value::Integer(INIT_HINT_COCOA_CHDIR_RESOURCES), Value::Boolean(false)]).unwrap();
+        glfw_init(vec![]).unwrap();
+        glfw_terminate(vec![]).unwrap();
+        glfw_init_hint(vec![Value::Integer(INIT_HINT_COCOA_MENUBAR), Value::Boolean(true)]).unwrap();
+        glfw_init_hint(vec![Value::Integer(INIT_HINT_COCOA_CHDIR_RESOURCES), Value::Boolean(true)]).unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    #[ignore = "requires GLFW 3.4 built with ANGLE"]
+    fn init_succeeds_with_the_angle_d3d11_backend() {
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        glfw_init_hint(vec![Value::Integer(INIT_HINT_ANGLE_PLATFORM_TYPE), Value::Integer(0x00037005)]).unwrap();
+        glfw_init(vec![]).unwrap();
+        glfw_terminate(vec![]).unwrap();
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn global_events_surface_without_any_window() {
+        let _session = Session::start();
+        // Ce que pousse le callback joystick de GLFW au branchement d'une manette
+        JOYSTICK_EVENTS.lock().unwrap().push((3, true));
+        glfw_post_user_event(vec![Value::Str("tick".into())]).unwrap();
+
+        let events = value_as_array(&glfw_poll_events_array(vec![]).unwrap()).unwrap();
+        let kinds: Vec<String> = events
+            .iter()
+            .map(|event| {
+                assert_eq!(int(map_field(event, "window").unwrap()), 0);
+                match map_field(event, "type").unwrap() {
+                    Value::Str(kind) => kind,
+                    _ => panic!("expected a type name"),
+                }
+            })
+            .collect();
+        assert_eq!(kinds, vec!["joystick", "user"]);
+        assert_eq!(int(map_field(&events[0], "jid").unwrap()), 3);
+
+        // Sans fenêtre ni événement, attendre puis repoller ne renvoie rien
+        assert!(matches!(glfw_wait_events_timeout(vec![Value::Float(0.0)]).unwrap(), Value::Boolean(false)));
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn creating_a_window_with_a_nul_title_is_an_error() {
+        let _session = Session::start();
+        let title = || Value::Str("bad\0title".into());
+        let err = error(glfw_create_window(vec![Value::Integer(64), Value::Integer(48), title()]));
+        assert!(err.contains("NUL byte at index 3"), "{}", err);
+        let err = error(glfw_create_window_ex(vec![Value::Integer(64), Value::Integer(48), title()]));
+        assert!(err.contains("NUL byte at index 3"), "{}", err);
+        assert!(STATE.lock().unwrap().as_ref().unwrap().0.windows.is_empty());
+    }
+
+    // Flot synthétique : clavier, souris, défilement et curseur mélangés sur quelques fenêtres
+    fn synthetic_event_flood(count: usize) -> Vec<(usize, f64, WindowEvent)> {
+        let keys = [glfw::Key::W, glfw::Key::A, glfw::Key::S, glfw::Key::D, glfw::Key::Unknown];
+        let actions = [glfw::Action::Press, glfw::Action::Repeat, glfw::Action::Release];
+        (0..count)
+            .map(|i| {
+                let time = i as f64 * 1e-4;
+                let action = actions[i / 4 % actions.len()];
+                let event = match i % 4 {
+                    0 => WindowEvent::Key(keys[i / 4 % keys.len()], 0, action, glfw::Modifiers::Shift),
+                    1 => WindowEvent::MouseButton(glfw::MouseButton::Button1, action, glfw::Modifiers::empty()),
+                    2 => WindowEvent::Scroll(0.5, -1.0),
+                    _ => WindowEvent::CursorPos(i as f64, (i * 2) as f64),
+                };
+                (i % 3 + 1, time, event)
+            })
+            .collect()
+    }
+
+    fn edges_snapshot(edges: &HashMap<usize, InputEdges>) -> Vec<(usize, Vec<i32>, Vec<i32>)> {
+        let mut snapshot: Vec<_> = edges
+            .iter()
+            .map(|(id, e)| {
+                let mut pressed: Vec<i32> = e.pressed.iter().copied().collect();
+                let mut released: Vec<i32> = e.released.iter().copied().collect();
+                pressed.sort();
+                released.sort();
+                (*id, pressed, released)
+            })
+            .collect();
+        snapshot.sort();
+        snapshot
+    }
+
+    fn assert_same_input(a: &(FrameInput, InputTracking), b: &(FrameInput, InputTracking)) {
+        assert_eq!(a.0.scroll_deltas, b.0.scroll_deltas);
+        assert_eq!(edges_snapshot(&a.0.key_edges), edges_snapshot(&b.0.key_edges));
+        assert_eq!(edges_snapshot(&a.0.mouse_button_edges), edges_snapshot(&b.0.mouse_button_edges));
+        assert_eq!(a.1.current_mods, b.1.current_mods);
+        assert_eq!(a.1.held_keys, b.1.held_keys);
+        assert_eq!(a.1.cursor_last, b.1.cursor_last);
+        assert_eq!(a.1.cursor_deltas, b.1.cursor_deltas);
+        assert_eq!(a.1.cursor_samples, b.1.cursor_samples);
+    }
+
+    #[test]
+    #[ignore = "timing benchmark: cargo test --release -- --ignored --nocapture"]
+    fn single_pass_pump_beats_one_pass_per_accumulator() {
+        let events = synthetic_event_flood(200_000);
+        let rounds = 20;
+
+        // Ce que fait pump_events : une seule boucle, accumulate_input par événement
+        let single_pass = |events: &[(usize, f64, WindowEvent)]| {
+            let mut out = (FrameInput::default(), InputTracking::default());
+            for (id, time, event) in events {
+                accumulate_input(&mut out.0, &mut out.1, *id, *time, event);
+            }
+            out
+        };
+        // Version naïve : une traversée complète par catégorie d'accumulateur
+        let multi_pass = |events: &[(usize, f64, WindowEvent)]| {
+            let mut out = (FrameInput::default(), InputTracking::default());
+            let categories: [fn(&WindowEvent) -> bool; 4] = [
+                |e| matches!(e, WindowEvent::Scroll(..)),
+                |e| matches!(e, WindowEvent::Key(..)),
+                |e| matches!(e, WindowEvent::MouseButton(..)),
+                |e| matches!(e, WindowEvent::CursorPos(..)),
+            ];
+            for wanted in categories {
+                for (id, time, event) in events.iter().filter(|(_, _, e)| wanted(e)) {
+                    accumulate_input(&mut out.0, &mut out.1, *id, *time, event);
+                }
+            }
+            out
+        };
+
+        // Les deux stratégies doivent produire exactement les mêmes accumulateurs
+        assert_same_input(&single_pass(&events), &multi_pass(&events));
+
+        let time = |run: &dyn Fn(&[(usize, f64, WindowEvent)]) -> (FrameInput, InputTracking)| {
+            let started = std::time::Instant::now();
+            for _ in 0..rounds {
+                std::hint::black_box(run(std::hint::black_box(&events)));
+            }
+            started.elapsed() / rounds
+        };
+        let single = time(&single_pass);
+        let multi = time(&multi_pass);
+        println!("{} events: single pass {:?}, one pass per accumulator {:?}", events.len(), single, multi);
+    }
+
+    #[test]
+    fn get_time_outside_a_session_is_an_error_and_records_nothing() {
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let pending = PENDING_ERRORS.lock().unwrap().len();
+        for _ in 0..3 {
+            assert_eq!(error(glfw_get_time(vec![])), "GLFW not initialized");
+        }
+        assert_eq!(PENDING_ERRORS.lock().unwrap().len(), pending);
+    }
+
+    #[test]
+    #[ignore = "requires a display or the NULL platform"]
+    fn get_time_follows_the_context_clock_within_a_session() {
+        let session = Session::start();
+        let before = STATE.lock().unwrap().as_ref().unwrap().0.context.get_time();
+        let time = float(glfw_get_time(vec![]).unwrap());
+        let after = STATE.lock().unwrap().as_ref().unwrap().0.context.get_time();
+        assert!(before <= time && time <= after, "{} not in [{}, {}]", time, before, after);
+
+        drop(session);
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(glfw_get_time(vec![]).is_err());
+        assert!(PENDING_ERRORS.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn event_filter_rejects_bits_outside_the_known_categories() {
+        let _serial = GLFW_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for mask in [EVENT_ALL + 1, 1 << 40, -1, i64::MIN] {
+            let err = error(glfw_set_event_filter(vec![Value::Integer(mask)]));
+            assert!(err.starts_with("Invalid event filter mask"), "{}: {}", mask, err);
+        }
+        // Masques valides : passent la validation et n'échouent que faute d'init
+        for mask in [0, EVENT_KEYS | EVENT_MOUSE, EVENT_ALL] {
+            assert_eq!(error(glfw_set_event_filter(vec![Value::Integer(mask)])), "GLFW not initialized");
+        }
+    }
 }